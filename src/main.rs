@@ -6,11 +6,12 @@ pub mod plonk_by_hand;
 use crate::field::Field;
 use crate::math::ecc::{CurvePoint, ExtensionCurvePoint, ECC};
 use crate::plonk_by_hand::constants;
+use crate::plonk_by_hand::proof::Proof;
 use crate::plonk_by_hand::prover::Prover;
-use crate::plonk_by_hand::public_coin::PublicCoin;
 use crate::plonk_by_hand::pythagorean_circuit_builder::PythagoreanCircuit;
+use crate::plonk_by_hand::setup::setup;
 use crate::plonk_by_hand::structured_reference_string::SRS;
-use crate::plonk_by_hand::verifier::Verifier;
+use crate::plonk_by_hand::verifier::verify;
 use math::field;
 
 fn main() {
@@ -18,21 +19,23 @@ fn main() {
     srs.generate_g_1_points();
     srs.generate_g_2_points();
 
-    let field = constants::FIELD_17.clone();
+    let field = constants::FIELD_17;
 
-    let pub_coin = constants::PUB_COIN.clone();
+    const A: u64 = 3;
+    const B: u64 = 4;
+    const C: u64 = 5;
 
-    const A: u32 = 3;
-    const B: u32 = 4;
-    const C: u32 = 5;
+    let (pk, vk) = setup(PythagoreanCircuit::new(field), &srs).expect("4 gates fit F_17");
 
-    let mut prover = Prover::new(field.clone(), vec![A, B, C], srs.copy());
-    prover.set_public_coin(pub_coin.clone());
-    let proof = prover.generate_proof();
+    let mut prover =
+        Prover::from_key(&pk, vec![A, B, C], srs.copy()).expect("one value per input wire");
+    let proof = prover
+        .generate_proof()
+        .expect("3, 4, 5 satisfies the circuit");
 
-    let mut verifier = Verifier::new(field, srs.copy());
-    verifier.preprocess();
-    verifier.provide_proof(pub_coin, proof);
+    // what the prover would send to a verifier elsewhere
+    let bytes = proof.to_bytes();
+    let received = Proof::from_bytes(&bytes, &srs.ecc, field).expect("a valid encoding");
 
-    assert!(verifier.verify_proof());
+    assert_eq!(verify(&vk, &[], &received), Ok(()));
 }
@@ -1,7 +1,10 @@
+pub mod big_field;
 pub mod complex_scalar;
 pub mod ecc;
+pub mod evaluations;
 pub mod field;
 pub mod matrix;
 pub mod pairing;
 pub mod polynomial;
 pub mod roots_of_unity;
+pub mod sha256;
@@ -0,0 +1,566 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// 256-bit prime field in Montgomery form, for scalar fields like BN254 Fr and BLS12-381 Fr.
+// Limbs are little-endian. Add, subtract and multiply do not branch on the values.
+// Not yet selectable per SRS, Prover or Verifier: those run on the u64 Field because
+// ECC multiplies by u64 scalars and its subgroup of order 17 fixes the scalar field.
+// Plugging this in behind a shared field trait needs a curve of matching order first.
+const LIMBS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BigField {
+    pub modulus: [u64; LIMBS],
+    // R mod p, where R = 2^256
+    r: [u64; LIMBS],
+    // R^2 mod p
+    r2: [u64; LIMBS],
+    // -p^-1 mod 2^64
+    inv: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BigFieldElement {
+    // Montgomery form: value * R mod p
+    limbs: [u64; LIMBS],
+    pub field: BigField,
+}
+
+pub const BN254_FR_MODULUS: [u64; LIMBS] = [
+    0x43e1f593f0000001,
+    0x2833e84879b97091,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+pub const BLS12_381_FR_MODULUS: [u64; LIMBS] = [
+    0xffffffff00000001,
+    0x53bda402fffe5bfe,
+    0x3339d80809a1d805,
+    0x73eda753299d7d48,
+];
+
+impl BigField {
+    // modulus must be an odd prime below 2^255
+    pub fn new(modulus: [u64; LIMBS]) -> BigField {
+        assert!(modulus[0] & 1 == 1, "modulus must be odd");
+        assert!(modulus[LIMBS - 1] >> 63 == 0, "modulus must be below 2^255");
+
+        // Newton iteration for p^-1 mod 2^64, each round doubles the correct bits
+        let mut inv: u64 = 1;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(modulus[0].wrapping_mul(inv)));
+        }
+
+        let mut field = BigField {
+            modulus,
+            r: [0; LIMBS],
+            r2: [0; LIMBS],
+            inv: inv.wrapping_neg(),
+        };
+
+        // 2^256 mod p and 2^512 mod p by repeated doubling of 1
+        let mut acc = [1, 0, 0, 0];
+        for _ in 0..256 {
+            acc = field.add_limbs(&acc, &acc);
+        }
+        field.r = acc;
+        for _ in 0..256 {
+            acc = field.add_limbs(&acc, &acc);
+        }
+        field.r2 = acc;
+
+        field
+    }
+
+    pub fn bn254_fr() -> BigField {
+        BigField::new(BN254_FR_MODULUS)
+    }
+
+    pub fn bls12_381_fr() -> BigField {
+        BigField::new(BLS12_381_FR_MODULUS)
+    }
+
+    pub fn element(&self, value: u64) -> BigFieldElement {
+        self.from_canonical(&[value, 0, 0, 0])
+            .expect("u64 values are always below a 255-bit modulus")
+    }
+
+    pub fn elements(&self, values: &[u64]) -> Vec<BigFieldElement> {
+        values.iter().map(|value| self.element(*value)).collect()
+    }
+
+    pub fn zero(&self) -> BigFieldElement {
+        BigFieldElement {
+            limbs: [0; LIMBS],
+            field: *self,
+        }
+    }
+
+    pub fn one(&self) -> BigFieldElement {
+        BigFieldElement {
+            limbs: self.r,
+            field: *self,
+        }
+    }
+
+    // Returns None if the limbs are not below the modulus
+    pub fn from_canonical(&self, limbs: &[u64; LIMBS]) -> Option<BigFieldElement> {
+        if !less_than(limbs, &self.modulus) {
+            return None;
+        }
+
+        Some(BigFieldElement {
+            limbs: self.mont_mul(limbs, &self.r2),
+            field: *self,
+        })
+    }
+
+    // Big-endian hex, with or without a 0x prefix
+    pub fn from_hex(&self, hex: &str) -> Option<BigFieldElement> {
+        let digits = hex.strip_prefix("0x").unwrap_or(hex);
+        // from_str_radix would also take a leading sign
+        if digits.is_empty()
+            || digits.len() > 16 * LIMBS
+            || !digits.bytes().all(|digit| digit.is_ascii_hexdigit())
+        {
+            return None;
+        }
+
+        let mut limbs = [0u64; LIMBS];
+        for (i, chunk) in digits.as_bytes().rchunks(16).enumerate() {
+            let chunk = std::str::from_utf8(chunk).ok()?;
+            limbs[i] = u64::from_str_radix(chunk, 16).ok()?;
+        }
+
+        self.from_canonical(&limbs)
+    }
+
+    pub fn add(&self, a: &BigFieldElement, b: &BigFieldElement) -> BigFieldElement {
+        BigFieldElement {
+            limbs: self.add_limbs(&a.limbs, &b.limbs),
+            field: *self,
+        }
+    }
+
+    pub fn subtract(&self, a: &BigFieldElement, b: &BigFieldElement) -> BigFieldElement {
+        BigFieldElement {
+            limbs: self.sub_limbs(&a.limbs, &b.limbs),
+            field: *self,
+        }
+    }
+
+    pub fn multiply(&self, a: &BigFieldElement, b: &BigFieldElement) -> BigFieldElement {
+        BigFieldElement {
+            limbs: self.mont_mul(&a.limbs, &b.limbs),
+            field: *self,
+        }
+    }
+
+    // None for division by zero
+    pub fn divide(&self, a: &BigFieldElement, b: &BigFieldElement) -> Option<BigFieldElement> {
+        Some(self.multiply(a, &self.multiplicative_inverse(b)?))
+    }
+
+    pub fn additive_inverse(&self, a: &BigFieldElement) -> BigFieldElement {
+        self.subtract(&self.zero(), a)
+    }
+
+    // Fermat's little theorem: a^(p-2), returns None for zero
+    pub fn multiplicative_inverse(&self, a: &BigFieldElement) -> Option<BigFieldElement> {
+        if a.is_zero() {
+            return None;
+        }
+        let exponent = sub_small(&self.modulus, 2);
+        Some(a.pow_limbs(&exponent))
+    }
+
+    fn add_limbs(&self, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut sum = [0u64; LIMBS];
+        let mut carry = 0;
+        for i in 0..LIMBS {
+            (sum[i], carry) = adc(a[i], b[i], carry);
+        }
+
+        // a, b < p < 2^255 so the sum cannot carry out of the top limb
+        let mut reduced = [0u64; LIMBS];
+        let mut borrow = 0;
+        for i in 0..LIMBS {
+            (reduced[i], borrow) = sbb(sum[i], self.modulus[i], borrow);
+        }
+
+        // borrow == 1 means sum < p, so keep the unreduced sum
+        select(borrow, &sum, &reduced)
+    }
+
+    fn sub_limbs(&self, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut difference = [0u64; LIMBS];
+        let mut borrow = 0;
+        for i in 0..LIMBS {
+            (difference[i], borrow) = sbb(a[i], b[i], borrow);
+        }
+
+        // add p back if we wrapped, masked so there is no branch
+        let mask = 0u64.wrapping_sub(borrow);
+        let mut carry = 0;
+        for (limb, modulus_limb) in difference.iter_mut().zip(self.modulus.iter()) {
+            (*limb, carry) = adc(*limb, modulus_limb & mask, carry);
+        }
+
+        difference
+    }
+
+    // CIOS Montgomery multiplication: a * b * R^-1 mod p
+    fn mont_mul(&self, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+        let mut t = [0u64; LIMBS + 2];
+
+        for b_limb in b.iter() {
+            let mut carry = 0;
+            for j in 0..LIMBS {
+                (t[j], carry) = mac(t[j], a[j], *b_limb, carry);
+            }
+            (t[LIMBS], carry) = adc(t[LIMBS], carry, 0);
+            t[LIMBS + 1] = carry;
+
+            let m = t[0].wrapping_mul(self.inv);
+            let (_, mut carry) = mac(t[0], m, self.modulus[0], 0);
+            for j in 1..LIMBS {
+                (t[j - 1], carry) = mac(t[j], m, self.modulus[j], carry);
+            }
+            (t[LIMBS - 1], carry) = adc(t[LIMBS], carry, 0);
+            t[LIMBS] = t[LIMBS + 1] + carry;
+        }
+
+        let result = [t[0], t[1], t[2], t[3]];
+        let mut reduced = [0u64; LIMBS];
+        let mut borrow = 0;
+        for i in 0..LIMBS {
+            (reduced[i], borrow) = sbb(result[i], self.modulus[i], borrow);
+        }
+
+        // keep the unreduced value only if it was already below p
+        let (_, keep) = sbb(t[LIMBS], 0, borrow);
+        select(keep, &result, &reduced)
+    }
+
+    // p - 1 = 2^s * t with t odd
+    fn two_adic_decomposition(&self) -> (u32, [u64; LIMBS]) {
+        let mut t = sub_small(&self.modulus, 1);
+        let mut s = 0;
+        while t[0] & 1 == 0 {
+            t = shift_right_one(&t);
+            s += 1;
+        }
+
+        (s, t)
+    }
+}
+
+impl BigFieldElement {
+    pub fn pow(&self, exponent: u64) -> BigFieldElement {
+        self.pow_limbs(&[exponent])
+    }
+
+    // Square-and-multiply over a little-endian exponent of any width
+    pub fn pow_limbs(&self, exponent: &[u64]) -> BigFieldElement {
+        let mut res = self.field.one();
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                res = res * res;
+                if (limb >> bit) & 1 == 1 {
+                    res *= *self;
+                }
+            }
+        }
+
+        res
+    }
+
+    pub fn inverse(&self) -> Option<BigFieldElement> {
+        self.field.multiplicative_inverse(self)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs == [0; LIMBS]
+    }
+
+    // 1 for non-zero squares, -1 for non-squares and 0 for zero
+    pub fn legendre_symbol(&self) -> i8 {
+        let exponent = shift_right_one(&sub_small(&self.field.modulus, 1));
+        let res = self.pow_limbs(&exponent);
+        if res.is_zero() {
+            0
+        } else if res == self.field.one() {
+            1
+        } else {
+            -1
+        }
+    }
+
+    // Tonelli-Shanks, returns None if self is not a square
+    pub fn sqrt(&self) -> Option<BigFieldElement> {
+        match self.legendre_symbol() {
+            0 => return Some(self.field.zero()),
+            -1 => return None,
+            _ => {}
+        }
+
+        let field = self.field;
+        let (s, t) = field.two_adic_decomposition();
+
+        let mut z = field.element(2);
+        while z.legendre_symbol() != -1 {
+            z += field.one();
+        }
+
+        let t_plus_one_halved = shift_right_one(&add_small(&t, 1));
+        let mut m = s;
+        let mut c = z.pow_limbs(&t);
+        let mut x = self.pow_limbs(&t_plus_one_halved);
+        let mut b = self.pow_limbs(&t);
+
+        while b != field.one() {
+            // find the least i with b^(2^i) == 1
+            let mut i = 0;
+            let mut b_pow = b;
+            while b_pow != field.one() {
+                b_pow = b_pow * b_pow;
+                i += 1;
+            }
+
+            let mut factor = c;
+            for _ in 0..(m - i - 1) {
+                factor = factor * factor;
+            }
+
+            m = i;
+            c = factor * factor;
+            x *= factor;
+            b *= c;
+        }
+
+        Some(x)
+    }
+
+    // Canonical (non-Montgomery) little-endian limbs
+    pub fn to_limbs(&self) -> [u64; LIMBS] {
+        self.field.mont_mul(&self.limbs, &[1, 0, 0, 0])
+    }
+}
+
+impl fmt::Debug for BigFieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for BigFieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x")?;
+        for limb in self.to_limbs().iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for BigFieldElement {
+    type Output = BigFieldElement;
+
+    fn add(self, rhs: BigFieldElement) -> BigFieldElement {
+        self.field.add(&self, &rhs)
+    }
+}
+
+impl Sub for BigFieldElement {
+    type Output = BigFieldElement;
+
+    fn sub(self, rhs: BigFieldElement) -> BigFieldElement {
+        self.field.subtract(&self, &rhs)
+    }
+}
+
+impl Mul for BigFieldElement {
+    type Output = BigFieldElement;
+
+    fn mul(self, rhs: BigFieldElement) -> BigFieldElement {
+        self.field.multiply(&self, &rhs)
+    }
+}
+
+impl Div for BigFieldElement {
+    type Output = BigFieldElement;
+
+    fn div(self, rhs: BigFieldElement) -> BigFieldElement {
+        self.field
+            .divide(&self, &rhs)
+            .expect("division by zero in field")
+    }
+}
+
+impl Neg for BigFieldElement {
+    type Output = BigFieldElement;
+
+    fn neg(self) -> BigFieldElement {
+        self.field.additive_inverse(&self)
+    }
+}
+
+impl AddAssign for BigFieldElement {
+    fn add_assign(&mut self, rhs: BigFieldElement) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for BigFieldElement {
+    fn sub_assign(&mut self, rhs: BigFieldElement) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for BigFieldElement {
+    fn mul_assign(&mut self, rhs: BigFieldElement) {
+        *self = *self * rhs;
+    }
+}
+
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let res = a as u128 + b as u128 + carry as u128;
+    (res as u64, (res >> 64) as u64)
+}
+
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let res = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (res as u64, (res >> 127) as u64)
+}
+
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let res = a as u128 + (b as u128 * c as u128) + carry as u128;
+    (res as u64, (res >> 64) as u64)
+}
+
+// choice == 1 selects a, choice == 0 selects b
+fn select(choice: u64, a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+    let mask = 0u64.wrapping_sub(choice);
+    let mut res = [0u64; LIMBS];
+    for i in 0..LIMBS {
+        res[i] = (a[i] & mask) | (b[i] & !mask);
+    }
+    res
+}
+
+fn less_than(a: &[u64; LIMBS], b: &[u64; LIMBS]) -> bool {
+    let mut borrow = 0;
+    for i in 0..LIMBS {
+        (_, borrow) = sbb(a[i], b[i], borrow);
+    }
+    borrow == 1
+}
+
+fn sub_small(a: &[u64; LIMBS], b: u64) -> [u64; LIMBS] {
+    let mut res = [0u64; LIMBS];
+    let mut borrow = b;
+    for i in 0..LIMBS {
+        (res[i], borrow) = sbb(a[i], 0, borrow);
+    }
+    res
+}
+
+fn add_small(a: &[u64; LIMBS], b: u64) -> [u64; LIMBS] {
+    let mut res = [0u64; LIMBS];
+    let mut carry = b;
+    for i in 0..LIMBS {
+        (res[i], carry) = adc(a[i], 0, carry);
+    }
+    res
+}
+
+fn shift_right_one(a: &[u64; LIMBS]) -> [u64; LIMBS] {
+    let mut res = [0u64; LIMBS];
+    for i in 0..LIMBS {
+        res[i] = a[i] >> 1;
+        if i + 1 < LIMBS {
+            res[i] |= a[i + 1] << 63;
+        }
+    }
+    res
+}
+
+#[test]
+fn test_big_field_arithmetic() {
+    for field in [BigField::bn254_fr(), BigField::bls12_381_fr()] {
+        let a = field.element(123456789);
+        let b = field.element(987654321);
+
+        assert_eq!(a + b, field.element(1111111110));
+        assert_eq!((a - b) + b, a);
+        assert_eq!(a * b, field.element(121932631112635269));
+        assert_eq!((a / b) * b, a);
+        assert_eq!(a * a.inverse().unwrap(), field.one());
+        assert_eq!(-a + a, field.zero());
+        assert_eq!(field.zero().inverse(), None);
+        assert_eq!(field.divide(&a, &field.zero()), None);
+        assert_eq!(a / a, field.one());
+
+        // (p - 1)^2 == 1
+        let minus_one = -field.one();
+        assert_eq!(minus_one * minus_one, field.one());
+        assert_eq!(field.from_canonical(&field.modulus), None);
+    }
+}
+
+#[test]
+fn test_big_field_from_hex() {
+    let field = BigField::bn254_fr();
+    assert_eq!(field.from_hex("0x1"), Some(field.one()));
+    assert_eq!(field.from_hex("ff"), Some(field.element(255)));
+    assert_eq!(field.from_hex("0x+1"), None);
+    assert_eq!(field.from_hex("-1"), None);
+    assert_eq!(field.from_hex("0x"), None);
+    assert_eq!(field.from_hex("0x1g"), None);
+}
+
+#[test]
+fn test_big_field_pow_against_known_values() {
+    let bn254 = BigField::bn254_fr();
+    assert_eq!(
+        bn254.element(123456789).pow(10),
+        bn254
+            .from_hex("0x15d160d37e628ba9ca8aff7dc2acbd68ad3116a3f1dd55315fffb41c9163c64f")
+            .unwrap()
+    );
+    assert_eq!(
+        bn254.element(2).inverse().unwrap().to_string(),
+        "0x183227397098d014dc2822db40c0ac2e9419f4243cdcb848a1f0fac9f8000001"
+    );
+
+    let bls = BigField::bls12_381_fr();
+    assert_eq!(
+        bls.element(123456789).pow(10),
+        bls.from_hex("25da3a3f94f841c037591add8467416eeab0256e24ea15cdce5a16fdf1641bd3")
+            .unwrap()
+    );
+    assert_eq!(
+        bls.element(2).inverse().unwrap().to_string(),
+        "0x39f6d3a994cebea4199cec0404d0ec02a9ded2017fff2dff7fffffff80000001"
+    );
+}
+
+#[test]
+fn test_big_field_sqrt() {
+    for field in [BigField::bn254_fr(), BigField::bls12_381_fr()] {
+        for value in [4u64, 9, 12345, 987654321] {
+            let a = field.element(value);
+            let square = a * a;
+            let root = square.sqrt().unwrap();
+            assert!(root == a || root == -a);
+        }
+
+        let mut non_residue = field.element(2);
+        while non_residue.legendre_symbol() != -1 {
+            non_residue += field.one();
+        }
+        assert_eq!(non_residue.sqrt(), None);
+        assert_eq!(field.zero().sqrt(), Some(field.zero()));
+    }
+}
@@ -1,9 +1,9 @@
 use crate::Field;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct ComplexScalar {
-    pub constant: u32,
-    pub u_term: u32,
+    pub constant: u64,
+    pub u_term: u64,
 }
 
 impl ComplexScalar {
@@ -35,22 +35,23 @@ impl ComplexScalar {
         c1.constant == c2.constant && c1.u_term == c2.u_term
     }
 
-    pub fn exponent(field: &Field, c: &ComplexScalar, exponent: u32) -> ComplexScalar {
-        let mut curr = 2;
+    pub fn exponent(field: &Field, c: &ComplexScalar, exponent: u64) -> ComplexScalar {
+        ComplexScalar::exponent_limbs(field, c, &[exponent])
+    }
+
+    // Square-and-multiply over a little-endian exponent of any width
+    pub fn exponent_limbs(field: &Field, c: &ComplexScalar, exponent: &[u64]) -> ComplexScalar {
         let mut res = ComplexScalar {
-            constant: c.constant,
-            u_term: c.u_term,
+            constant: 1,
+            u_term: 0,
         };
-        // 1 -> 2 -> 4 -> 8 -> 16 -> 32 -> 64 -> 128 -> 256 -> 512
-        while curr < exponent {
-            res = ComplexScalar::multiply(field, &res, &res);
-            curr *= 2;
-        }
-
-        curr /= 2;
-        while curr < exponent {
-            res = ComplexScalar::multiply(field, c, &res);
-            curr += 1;
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                res = ComplexScalar::multiply(field, &res, &res);
+                if (limb >> bit) & 1 == 1 {
+                    res = ComplexScalar::multiply(field, &res, c);
+                }
+            }
         }
 
         res
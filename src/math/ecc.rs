@@ -1,7 +1,7 @@
 use crate::math::complex_scalar::ComplexScalar;
 use crate::math::field::Field;
 
-const INFINITY: u32 = 99999999;
+const INFINITY: u64 = 99999999;
 
 // Curve: y^2 = x^3 + 3
 #[derive(Clone)]
@@ -11,12 +11,12 @@ pub struct ECC {
 
 #[derive(Debug, Default, Clone)]
 pub struct CurvePoint {
-    pub x: u32,
-    pub y: u32,
+    pub x: u64,
+    pub y: u64,
 }
 
 impl CurvePoint {
-    pub fn new(x: u32, y: u32) -> CurvePoint {
+    pub fn new(x: u64, y: u64) -> CurvePoint {
         CurvePoint { x, y }
     }
 
@@ -34,13 +34,13 @@ impl CurvePoint {
 
 #[derive(Debug, Default, Clone)]
 pub struct ExtensionCurvePoint {
-    pub x: u32,
-    pub y: u32,
+    pub x: u64,
+    pub y: u64,
     pub u: bool,
 }
 
 impl ExtensionCurvePoint {
-    pub fn new(x: u32, y: u32, u: bool) -> ExtensionCurvePoint {
+    pub fn new(x: u64, y: u64, u: bool) -> ExtensionCurvePoint {
         ExtensionCurvePoint { x, y, u }
     }
 
@@ -51,9 +51,7 @@ impl ExtensionCurvePoint {
 
 impl ECC {
     pub fn copy(&self) -> ECC {
-        ECC {
-            field: self.field.clone(),
-        }
+        ECC { field: self.field }
     }
 
     // only used if p1 != p2
@@ -84,6 +82,10 @@ impl ECC {
     }
 
     pub fn double(&self, p: &CurvePoint) -> CurvePoint {
+        // the tangent at a point of order 2 is vertical
+        if p.y == 0 {
+            return CurvePoint::point_at_infinity();
+        }
         let (new_x, new_y, _u) = self.double_any(p.x, p.y, false);
 
         CurvePoint::new(new_x, new_y)
@@ -95,7 +97,7 @@ impl ECC {
         ExtensionCurvePoint::new(new_x, new_y, u)
     }
 
-    fn double_any(&self, x: u32, y: u32, u: bool) -> (u32, u32, bool) {
+    fn double_any(&self, x: u64, y: u64, u: bool) -> (u64, u64, bool) {
         if !u && CurvePoint::equals(&CurvePoint::new(x, y), &CurvePoint::point_at_infinity()) {
             return (x, y, false);
         }
@@ -130,7 +132,66 @@ impl ECC {
         (new_x, new_y, u)
     }
 
+    pub fn is_on_curve(&self, point: &CurvePoint) -> bool {
+        if point.x >= self.field.order || point.y >= self.field.order {
+            return false;
+        }
+        // must satisfy y^2 = x^3 + 3
+        let left = self.field.exponent(point.y, 2);
+        let right = self.field.add(self.field.exponent(point.x, 3), 3);
+
+        left == right
+    }
+
+    // Whether order * point is the point at infinity. The curve has 102 points, so a
+    // point can be on it and still outside the subgroup of order 17.
+    pub fn in_subgroup(&self, point: &CurvePoint, order: u64) -> bool {
+        let infinity = CurvePoint::point_at_infinity();
+        CurvePoint::equals(point, &infinity)
+            || (self.is_on_curve(point)
+                && CurvePoint::equals(&self.multiply(order, point), &infinity))
+    }
+
+    // A u point is (x, y * u) with u^2 = -2, so it must satisfy -2 * y^2 = x^3 + 3
+    pub fn is_on_extension_curve(&self, point: &ExtensionCurvePoint) -> bool {
+        if !point.u {
+            return self.is_on_curve(&CurvePoint::new(point.x, point.y));
+        }
+        if point.x >= self.field.order || point.y >= self.field.order {
+            return false;
+        }
+        let left = self.field.multiply(
+            self.field.additive_inverse(2),
+            self.field.exponent(point.y, 2),
+        );
+        let right = self.field.add(self.field.exponent(point.x, 3), 3);
+
+        left == right
+    }
+
+    // Recovers y from x, picking the root whose parity matches y_is_odd.
+    // Returns None if x is not the x-coordinate of a curve point.
+    pub fn point_from_x(&self, x: u64, y_is_odd: bool) -> Option<CurvePoint> {
+        if x >= self.field.order {
+            return None;
+        }
+
+        let y_squared = self.field.add(self.field.exponent(x, 3), 3);
+        let y = self.field.sqrt(y_squared)?;
+        let y = if (y % 2 == 1) == y_is_odd {
+            y
+        } else {
+            self.field.additive_inverse(y)
+        };
+
+        Some(CurvePoint::new(x, y))
+    }
+
     pub fn inversion(&self, p: &CurvePoint) -> CurvePoint {
+        if CurvePoint::equals(p, &CurvePoint::point_at_infinity()) {
+            return p.clone();
+        }
+
         CurvePoint {
             x: p.x,
             y: self.field.additive_inverse(p.y),
@@ -141,12 +202,12 @@ impl ECC {
     // 14G = 7 (2G) -> 6 (3G) -> 3 (6G) -> 2 (7G) -> 14G
     // 15G = 14 (G) -> 7 (2G) ->
     // 15G = G + 14G
-    pub fn multiply(&self, scalar: u32, p1: &CurvePoint) -> CurvePoint {
+    pub fn multiply(&self, scalar: u64, p1: &CurvePoint) -> CurvePoint {
         if scalar == 0 {
             CurvePoint::point_at_infinity()
         } else if scalar == 1 {
             p1.clone()
-        } else if scalar % 2 == 0 {
+        } else if scalar.is_multiple_of(2) {
             let doubled = self.double(p1);
             self.multiply(scalar / 2, &doubled)
         } else {
@@ -155,7 +216,7 @@ impl ECC {
     }
 
     // outputs x factor, y factor, and constant -> for y^2
-    pub fn get_line_between_points(&self, p1: &CurvePoint, p2: &CurvePoint) -> (u32, u32, u32) {
+    pub fn get_line_between_points(&self, p1: &CurvePoint, p2: &CurvePoint) -> (u64, u64, u64) {
         let m_numerator = self.field.subtract(p2.y, p1.y);
         let m_denominator = self.field.subtract(p2.x, p1.x);
 
@@ -191,9 +252,9 @@ impl ECC {
     pub fn plug_extension_point_in_equation(
         &self,
         p: &ExtensionCurvePoint,
-        x_factor: u32,
-        y_factor: u32,
-        constant: u32,
+        x_factor: u64,
+        y_factor: u64,
+        constant: u64,
     ) -> ComplexScalar {
         ComplexScalar {
             constant: self.field.add(self.field.multiply(x_factor, p.x), constant),
@@ -201,21 +262,17 @@ impl ECC {
         }
     }
 
+    // The discrete log of p to the base generator, or None if p is not a multiple of it
     pub fn get_generator_multiple(
         &self,
         p: &CurvePoint,
         generator: &CurvePoint,
-        order: u32,
-    ) -> u32 {
-        let mut ans = 0;
-        for i in 1..order {
-            let new_point = self.multiply(i, generator);
-            if CurvePoint::equals(p, &new_point) {
-                ans = i;
-                break;
-            }
+        order: u64,
+    ) -> Option<u64> {
+        if CurvePoint::equals(p, &CurvePoint::point_at_infinity()) {
+            return Some(0);
         }
-        ans
+        (1..order).find(|i| CurvePoint::equals(p, &self.multiply(*i, generator)))
     }
 }
 
@@ -241,6 +298,54 @@ fn test_multiply() {
     ));
 }
 
+#[test]
+fn test_point_from_x() {
+    let ecc = ECC {
+        field: Field { order: 101 },
+    };
+    let generator = CurvePoint::new(1, 2);
+
+    for i in 1..17 {
+        let point = ecc.multiply(i, &generator);
+        let recovered = ecc.point_from_x(point.x, point.y % 2 == 1).unwrap();
+        assert!(CurvePoint::equals(&point, &recovered));
+        assert!(ecc.is_on_curve(&recovered));
+    }
+
+    // 4^3 + 3 = 67 is not a square mod 101
+    assert!(ecc.point_from_x(4, false).is_none());
+    assert!(ecc.point_from_x(101, false).is_none());
+}
+
+#[test]
+fn test_subgroup() {
+    let ecc = ECC {
+        field: Field { order: 101 },
+    };
+    let generator = CurvePoint::new(1, 2);
+
+    for i in 0..17 {
+        let point = ecc.multiply(i, &generator);
+        assert!(ecc.in_subgroup(&point, 17));
+        assert_eq!(ecc.get_generator_multiple(&point, &generator, 17), Some(i));
+    }
+
+    // (48, 0) has order 2 and (27, 30) has order 3, so neither is a multiple of g
+    for point in [CurvePoint::new(48, 0), CurvePoint::new(27, 30)] {
+        assert!(ecc.is_on_curve(&point));
+        assert!(!ecc.in_subgroup(&point, 17));
+        assert_eq!(ecc.get_generator_multiple(&point, &generator, 17), None);
+    }
+    assert!(CurvePoint::equals(
+        &ecc.double(&CurvePoint::new(48, 0)),
+        &CurvePoint::point_at_infinity()
+    ));
+    assert!(CurvePoint::equals(
+        &ecc.multiply(3, &CurvePoint::new(27, 30)),
+        &CurvePoint::point_at_infinity()
+    ));
+}
+
 #[test]
 fn test_extension_double() {
     let ecc = ECC {
@@ -251,6 +356,12 @@ fn test_extension_double() {
     let doubled = ecc.double_extension(&g2);
     let expected = ExtensionCurvePoint::new(90, 82, true);
     assert!(ExtensionCurvePoint::equals(&expected, &doubled));
+
+    assert!(ecc.is_on_extension_curve(&g2));
+    assert!(ecc.is_on_extension_curve(&doubled));
+    assert!(!ecc.is_on_extension_curve(&ExtensionCurvePoint::new(36, 32, true)));
+    assert!(!ecc.is_on_extension_curve(&ExtensionCurvePoint::new(36, 31, false)));
+    assert!(!ecc.is_on_extension_curve(&ExtensionCurvePoint::new(137, 31, true)));
 }
 
 #[test]
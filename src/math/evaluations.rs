@@ -0,0 +1,237 @@
+use crate::math::field::FieldElement;
+use crate::math::polynomial::Polynomial;
+use crate::math::roots_of_unity::EvaluationDomain;
+use std::ops::{Add, Mul, Sub};
+
+// A polynomial of degree < n in Lagrange form: its values at shift * ω^i for every
+// point ω^i of an n-element domain. shift = 1 is the domain itself.
+#[derive(Clone, Debug)]
+pub struct Evaluations<'a> {
+    pub values: Vec<FieldElement>,
+    pub domain: &'a EvaluationDomain,
+    pub shift: FieldElement,
+}
+
+impl<'a> Evaluations<'a> {
+    pub fn from_values(
+        values: Vec<FieldElement>,
+        domain: &'a EvaluationDomain,
+        shift: FieldElement,
+    ) -> Evaluations<'a> {
+        assert_eq!(values.len(), domain.size, "need one value per domain point");
+
+        Evaluations {
+            values,
+            domain,
+            shift,
+        }
+    }
+
+    pub fn from_polynomial(poly: &Polynomial, domain: &'a EvaluationDomain) -> Evaluations<'a> {
+        Evaluations::from_polynomial_on_coset(poly, domain, domain.field.one())
+    }
+
+    pub fn from_polynomial_on_coset(
+        poly: &Polynomial,
+        domain: &'a EvaluationDomain,
+        shift: FieldElement,
+    ) -> Evaluations<'a> {
+        Evaluations {
+            values: domain.coset_ntt(poly.coefficients(), shift),
+            domain,
+            shift,
+        }
+    }
+
+    // The polynomial X, i.e. the evaluation points themselves
+    pub fn identity(domain: &'a EvaluationDomain, shift: FieldElement) -> Evaluations<'a> {
+        Evaluations {
+            values: domain.coset(shift),
+            domain,
+            shift,
+        }
+    }
+
+    // Coefficients of the unique polynomial of degree < n with these values
+    pub fn interpolate(&self) -> Polynomial {
+        Polynomial::new(
+            self.domain.coset_inverse_ntt(&self.values, self.shift),
+            self.domain.field,
+        )
+    }
+
+    // Barycentric evaluation at any x. The points x_i = s * ω^i are the roots of
+    // X^n - s^n, so f(x) = (x^n - s^n) / (n * s^n) * sum_i f_i * x_i / (x - x_i).
+    pub fn evaluate(&self, x: FieldElement) -> FieldElement {
+        let field = self.domain.field;
+        let points = self.domain.coset(self.shift);
+
+        let differences: Vec<FieldElement> = points.iter().map(|x_i| x - *x_i).collect();
+        let Some(difference_inverses) = field.batch_inverse(&differences) else {
+            // x is one of the points
+            let i = differences.iter().position(|d| d.is_zero()).unwrap();
+            return self.values[i];
+        };
+
+        let sum = self
+            .values
+            .iter()
+            .zip(points.iter())
+            .zip(difference_inverses.iter())
+            .fold(field.zero(), |acc, ((f_i, x_i), inv)| {
+                acc + *f_i * *x_i * *inv
+            });
+
+        let shift_n = self.shift.pow(self.domain.size as u64);
+        let scale = (x.pow(self.domain.size as u64) - shift_n)
+            * self.domain.size_inv
+            * shift_n.inverse().expect("coset shift must be non-zero");
+
+        scale * sum
+    }
+
+    fn zip_with(
+        &self,
+        other: &Evaluations<'a>,
+        op: impl Fn(FieldElement, FieldElement) -> FieldElement,
+    ) -> Evaluations<'a> {
+        assert!(
+            self.domain.size == other.domain.size && self.shift == other.shift,
+            "evaluations must be over the same points"
+        );
+
+        Evaluations {
+            values: self
+                .values
+                .iter()
+                .zip(other.values.iter())
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+            domain: self.domain,
+            shift: self.shift,
+        }
+    }
+
+    fn map(&self, op: impl Fn(FieldElement) -> FieldElement) -> Evaluations<'a> {
+        Evaluations {
+            values: self.values.iter().map(|a| op(*a)).collect(),
+            domain: self.domain,
+            shift: self.shift,
+        }
+    }
+}
+
+// Pointwise operators for every mix of owned and borrowed operands, so expressions
+// like `&a * &b + c * beta` chain without temporaries
+macro_rules! impl_pointwise_op {
+    ($trait:ident, $method:ident, $op:tt) => {
+        impl<'a> $trait<&Evaluations<'a>> for &Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, other: &Evaluations<'a>) -> Evaluations<'a> {
+                self.zip_with(other, |a, b| a $op b)
+            }
+        }
+
+        impl<'a> $trait<Evaluations<'a>> for &Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, other: Evaluations<'a>) -> Evaluations<'a> {
+                self $op &other
+            }
+        }
+
+        impl<'a> $trait<&Evaluations<'a>> for Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, other: &Evaluations<'a>) -> Evaluations<'a> {
+                &self $op other
+            }
+        }
+
+        impl<'a> $trait<Evaluations<'a>> for Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, other: Evaluations<'a>) -> Evaluations<'a> {
+                &self $op &other
+            }
+        }
+
+        // a scalar is the constant polynomial
+        impl<'a> $trait<FieldElement> for &Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, scalar: FieldElement) -> Evaluations<'a> {
+                self.map(|a| a $op scalar)
+            }
+        }
+
+        impl<'a> $trait<FieldElement> for Evaluations<'a> {
+            type Output = Evaluations<'a>;
+
+            fn $method(self, scalar: FieldElement) -> Evaluations<'a> {
+                &self $op scalar
+            }
+        }
+    };
+}
+
+impl_pointwise_op!(Add, add, +);
+impl_pointwise_op!(Sub, sub, -);
+impl_pointwise_op!(Mul, mul, *);
+
+#[test]
+fn test_evaluations_round_trip() {
+    use crate::math::field::Field;
+
+    let field = Field::new(17);
+    let domain = EvaluationDomain::new(4, field).unwrap();
+
+    // the article's left wire values and polynomial
+    let evals = Evaluations::from_values(field.elements(&[3, 4, 5, 9]), &domain, field.one());
+    let poly = evals.interpolate();
+    assert_eq!(poly.coefficients(), vec![1, 13, 3, 3]);
+    assert_eq!(
+        Evaluations::from_polynomial(&poly, &domain).values,
+        vec![3, 4, 5, 9]
+    );
+
+    for x in 0..17 {
+        let x = field.element(x);
+        assert_eq!(evals.evaluate(x), poly.eval(x));
+    }
+
+    let shift = field.element(2);
+    let coset_evals = Evaluations::from_polynomial_on_coset(&poly, &domain, shift);
+    assert_eq!(coset_evals.interpolate(), poly);
+    for x in 0..17 {
+        let x = field.element(x);
+        assert_eq!(coset_evals.evaluate(x), poly.eval(x));
+    }
+}
+
+#[test]
+fn test_evaluations_pointwise_arithmetic() {
+    use crate::math::field::Field;
+
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let domain = EvaluationDomain::new(16, field).unwrap();
+    let shift = field.element(7);
+
+    let poly1 = Polynomial::new(field.elements(&[1, 2, 3, 4, 5, 6]), field);
+    let poly2 = Polynomial::new(field.elements(&[9, 8, 7, 6, 5, 4, 3]), field);
+    let evals1 = Evaluations::from_polynomial_on_coset(&poly1, &domain, shift);
+    let evals2 = Evaluations::from_polynomial_on_coset(&poly2, &domain, shift);
+
+    // deg(poly1 * poly2) = 11 < 16, so the pointwise product interpolates exactly
+    assert_eq!((&evals1 * &evals2).interpolate(), &poly1 * &poly2);
+    assert_eq!((&evals2 - &evals1).interpolate(), &poly2 - &poly1);
+
+    let x = Evaluations::identity(&domain, shift);
+    let shifted = &x * field.element(3) + field.element(5);
+    assert_eq!(shifted.interpolate().coefficients(), vec![5, 3]);
+    assert_eq!(
+        (&evals1 + &x).evaluate(field.element(2)),
+        field.element(323)
+    );
+}
@@ -1,52 +1,491 @@
-// Note: Does not handle overflows
-#[derive(Clone)]
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// Products are computed in u128, so any prime order that fits in a u64 is safe from overflow
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Field {
-    pub order: u32,
+    pub order: u64,
 }
 
 impl Field {
-    pub fn add(&self, a: u32, b: u32) -> u32 {
-        (a + b) % self.order
+    pub const fn new(order: u64) -> Field {
+        Field { order }
+    }
+
+    pub const fn element(&self, value: u64) -> FieldElement {
+        FieldElement {
+            value: value % self.order,
+            field: *self,
+        }
+    }
+
+    pub fn elements(&self, values: &[u64]) -> Vec<FieldElement> {
+        values.iter().map(|value| self.element(*value)).collect()
+    }
+
+    pub const fn zero(&self) -> FieldElement {
+        self.element(0)
+    }
+
+    pub const fn one(&self) -> FieldElement {
+        self.element(1)
     }
 
-    pub fn subtract(&self, a: u32, b: u32) -> u32 {
+    pub fn add(&self, a: u64, b: u64) -> u64 {
+        ((a as u128 + b as u128) % self.order as u128) as u64
+    }
+
+    pub fn subtract(&self, a: u64, b: u64) -> u64 {
         self.add(a, self.additive_inverse(b))
     }
 
-    pub fn multiply(&self, a: u32, b: u32) -> u32 {
-        (a * b) % self.order
+    pub fn multiply(&self, a: u64, b: u64) -> u64 {
+        ((a as u128 * b as u128) % self.order as u128) as u64
     }
 
-    pub fn divide(&self, a: u32, b: u32) -> u32 {
-        self.multiply(a, self.multiplicative_inverse(b))
+    pub fn divide(&self, a: u64, b: u64) -> u64 {
+        let b_inv = self
+            .multiplicative_inverse(b)
+            .expect("division by zero in field");
+        self.multiply(a, b_inv)
     }
 
-    pub fn exponent(&self, a: u32, b: u32) -> u32 {
-        if b == 0 {
-            return 1;
-        }
+    pub fn exponent(&self, a: u64, b: u64) -> u64 {
+        self.exponent_limbs(a, &[b])
+    }
 
-        let mut res = a;
-        for _i in 1..b {
-            res = self.multiply(res, a);
+    // Square-and-multiply over a little-endian exponent of any width
+    pub fn exponent_limbs(&self, a: u64, b: &[u64]) -> u64 {
+        let mut res = 1 % self.order;
+        for limb in b.iter().rev() {
+            for bit in (0..64).rev() {
+                res = self.multiply(res, res);
+                if (limb >> bit) & 1 == 1 {
+                    res = self.multiply(res, a);
+                }
+            }
         }
 
-        res % self.order
+        res
     }
 
-    pub fn additive_inverse(&self, a: u32) -> u32 {
+    pub fn additive_inverse(&self, a: u64) -> u64 {
         (self.order - (a % self.order)) % self.order
     }
 
-    pub fn multiplicative_inverse(&self, a: u32) -> u32 {
-        // TODO: change to euclidean algo
-        let mut inv = 0;
-        for i in 0..self.order {
-            if self.multiply(i, a) == 1 {
-                inv = i;
-                break;
+    // Extended Euclidean algorithm, returns None for zero
+    pub fn multiplicative_inverse(&self, a: u64) -> Option<u64> {
+        let a = a % self.order;
+        if a == 0 {
+            return None;
+        }
+
+        // invariant: old_s * a == old_r (mod order)
+        let (mut old_r, mut r) = (a as i128, self.order as i128);
+        let (mut old_s, mut s) = (1i128, 0i128);
+        while r != 0 {
+            let quotient = old_r / r;
+            (old_r, r) = (r, old_r - quotient * r);
+            (old_s, s) = (s, old_s - quotient * s);
+        }
+
+        Some(old_s.rem_euclid(self.order as i128) as u64)
+    }
+
+    // Fermat's little theorem: a^(p-2) == a^-1, returns None for zero
+    pub fn fermat_inverse(&self, a: u64) -> Option<u64> {
+        if a.is_multiple_of(self.order) {
+            return None;
+        }
+
+        Some(self.exponent(a, self.order - 2))
+    }
+
+    // 1 for non-zero squares, -1 for non-squares and 0 for zero
+    pub fn legendre_symbol(&self, a: u64) -> i8 {
+        let res = self.exponent(a, (self.order - 1) / 2);
+        if res == 0 {
+            0
+        } else if res == 1 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    // Tonelli-Shanks, returns None if a is not a square
+    pub fn sqrt(&self, a: u64) -> Option<u64> {
+        let a = a % self.order;
+        match self.legendre_symbol(a) {
+            0 => return Some(0),
+            -1 => return None,
+            _ => {}
+        }
+
+        if self.order == 2 {
+            return Some(a);
+        }
+
+        // order - 1 = 2^s * t with t odd
+        let mut s = 0;
+        let mut t = self.order - 1;
+        while t.is_multiple_of(2) {
+            t /= 2;
+            s += 1;
+        }
+
+        let mut z = 2;
+        while self.legendre_symbol(z) != -1 {
+            z += 1;
+        }
+
+        let mut m = s;
+        let mut c = self.exponent(z, t);
+        let mut x = self.exponent(a, t.div_ceil(2));
+        let mut b = self.exponent(a, t);
+
+        while b != 1 {
+            // find the least i with b^(2^i) == 1
+            let mut i = 0;
+            let mut b_pow = b;
+            while b_pow != 1 {
+                b_pow = self.multiply(b_pow, b_pow);
+                i += 1;
             }
+
+            let mut factor = c;
+            for _ in 0..(m - i - 1) {
+                factor = self.multiply(factor, factor);
+            }
+
+            m = i;
+            c = self.multiply(factor, factor);
+            x = self.multiply(x, factor);
+            b = self.multiply(b, c);
+        }
+
+        Some(x)
+    }
+
+    // Miller-Rabin with the first twelve prime bases, which is exact for every u64
+    pub fn is_prime_order(&self) -> bool {
+        let n = self.order;
+        if n < 2 {
+            return false;
+        }
+        const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+        if let Some(base) = BASES.iter().find(|base| n.is_multiple_of(**base)) {
+            return n == *base;
         }
-        inv
+
+        // n - 1 = 2^s * d with d odd
+        let s = (n - 1).trailing_zeros();
+        let d = (n - 1) >> s;
+        BASES.iter().all(|base| {
+            let mut x = self.exponent(*base, d);
+            if x == 1 || x == n - 1 {
+                return true;
+            }
+            for _ in 1..s {
+                x = self.multiply(x, x);
+                if x == n - 1 {
+                    return true;
+                }
+            }
+            false
+        })
     }
+
+    // Montgomery's trick: inverts every element with a single field inversion.
+    // Returns None if any element is zero.
+    pub fn batch_inverse(&self, values: &[FieldElement]) -> Option<Vec<FieldElement>> {
+        // prefix_products[i] = values[0] * ... * values[i - 1]
+        let mut prefix_products = Vec::with_capacity(values.len());
+        let mut acc = self.one();
+        for value in values.iter() {
+            prefix_products.push(acc);
+            acc *= *value;
+        }
+
+        let mut acc_inv = acc.inverse()?;
+        let mut res = vec![self.zero(); values.len()];
+        for i in (0..values.len()).rev() {
+            res[i] = acc_inv * prefix_products[i];
+            acc_inv *= values[i];
+        }
+
+        Some(res)
+    }
+}
+
+// An element of a prime field. Operators panic in debug builds if the two sides
+// belong to different fields.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldElement {
+    pub value: u64,
+    pub field: Field,
+}
+
+impl FieldElement {
+    pub fn pow(&self, exponent: u64) -> FieldElement {
+        FieldElement {
+            value: self.field.exponent(self.value, exponent),
+            field: self.field,
+        }
+    }
+
+    pub fn pow_limbs(&self, exponent: &[u64]) -> FieldElement {
+        FieldElement {
+            value: self.field.exponent_limbs(self.value, exponent),
+            field: self.field,
+        }
+    }
+
+    pub fn inverse(&self) -> Option<FieldElement> {
+        self.field
+            .multiplicative_inverse(self.value)
+            .map(|value| FieldElement {
+                value,
+                field: self.field,
+            })
+    }
+
+    pub fn sqrt(&self) -> Option<FieldElement> {
+        self.field.sqrt(self.value).map(|value| FieldElement {
+            value,
+            field: self.field,
+        })
+    }
+
+    pub fn legendre_symbol(&self) -> i8 {
+        self.field.legendre_symbol(self.value)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    fn check_same_field(&self, other: &FieldElement) {
+        debug_assert_eq!(
+            self.field.order, other.field.order,
+            "field elements belong to different fields"
+        );
+    }
+}
+
+impl fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl PartialEq<u64> for FieldElement {
+    fn eq(&self, other: &u64) -> bool {
+        self.value == *other
+    }
+}
+
+impl PartialEq<FieldElement> for u64 {
+    fn eq(&self, other: &FieldElement) -> bool {
+        *self == other.value
+    }
+}
+
+impl Add for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: FieldElement) -> FieldElement {
+        self.check_same_field(&rhs);
+        FieldElement {
+            value: self.field.add(self.value, rhs.value),
+            field: self.field,
+        }
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: FieldElement) -> FieldElement {
+        self.check_same_field(&rhs);
+        FieldElement {
+            value: self.field.subtract(self.value, rhs.value),
+            field: self.field,
+        }
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: FieldElement) -> FieldElement {
+        self.check_same_field(&rhs);
+        FieldElement {
+            value: self.field.multiply(self.value, rhs.value),
+            field: self.field,
+        }
+    }
+}
+
+impl Div for FieldElement {
+    type Output = FieldElement;
+
+    fn div(self, rhs: FieldElement) -> FieldElement {
+        self.check_same_field(&rhs);
+        FieldElement {
+            value: self.field.divide(self.value, rhs.value),
+            field: self.field,
+        }
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        FieldElement {
+            value: self.field.additive_inverse(self.value),
+            field: self.field,
+        }
+    }
+}
+
+impl AddAssign for FieldElement {
+    fn add_assign(&mut self, rhs: FieldElement) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for FieldElement {
+    fn sub_assign(&mut self, rhs: FieldElement) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for FieldElement {
+    fn mul_assign(&mut self, rhs: FieldElement) {
+        *self = *self * rhs;
+    }
+}
+
+#[test]
+fn test_element_arithmetic() {
+    let field = Field::new(17);
+    let a = field.element(15);
+    let b = field.element(4);
+
+    assert_eq!(a + b, 2);
+    assert_eq!(a - b, 11);
+    assert_eq!(b - a, 6);
+    assert_eq!(a * b, 9);
+    assert_eq!((a / b) * b, a);
+    assert_eq!(-a, 2);
+    assert_eq!(b.pow(2), 16);
+    assert_eq!(field.element(20), 3);
+}
+
+#[test]
+fn test_inverses() {
+    let field = Field::new(17);
+
+    for a in 1..field.order {
+        let inv = field.multiplicative_inverse(a).unwrap();
+        assert_eq!(field.multiply(a, inv), 1);
+        assert_eq!(field.fermat_inverse(a), Some(inv));
+    }
+
+    assert_eq!(field.multiplicative_inverse(0), None);
+    assert_eq!(field.fermat_inverse(17), None);
+    assert_eq!(field.zero().inverse(), None);
+}
+
+#[test]
+fn test_batch_inverse() {
+    let field = Field::new(101);
+    let values = field.elements(&[1, 2, 3, 50, 99, 100]);
+
+    let inverses = field.batch_inverse(&values).unwrap();
+    for (value, inverse) in values.iter().zip(inverses.iter()) {
+        assert_eq!(*value * *inverse, field.one());
+    }
+
+    assert_eq!(field.batch_inverse(&[]), Some(Vec::new()));
+    assert_eq!(field.batch_inverse(&field.elements(&[3, 0, 5])), None);
+}
+
+#[test]
+fn test_exponent() {
+    let field = Field::new(101);
+    assert_eq!(field.exponent(7, 0), 1);
+    assert_eq!(field.exponent(7, 1), 7);
+    assert_eq!(field.exponent(2, 10), 1024 % 101);
+    // exponents reduce mod 100 since 2^100 == 1
+    assert_eq!(
+        field.exponent_limbs(2, &[1, 1]),
+        field.exponent(2, (u64::MAX % 100) + 2)
+    );
+
+    let goldilocks = Field::new(0xffff_ffff_0000_0001);
+    assert_eq!(goldilocks.exponent(3, goldilocks.order - 1), 1);
+}
+
+#[test]
+fn test_legendre_and_sqrt() {
+    for field in [
+        Field::new(17),
+        Field::new(101),
+        Field::new(0xffff_ffff_0000_0001),
+    ] {
+        for a in 1..field.order.min(50) {
+            let square = field.multiply(a, a);
+            assert_eq!(field.legendre_symbol(square), 1);
+
+            let root = field.sqrt(square).unwrap();
+            assert!(root == a || root == field.additive_inverse(a));
+        }
+        assert_eq!(field.sqrt(0), Some(0));
+    }
+
+    let field = Field::new(17);
+    // squares mod 17: 1, 2, 4, 8, 9, 13, 15, 16
+    for a in [3, 5, 6, 7, 10, 11, 12, 14] {
+        assert_eq!(field.legendre_symbol(a), -1);
+        assert_eq!(field.element(a).sqrt(), None);
+    }
+}
+
+#[test]
+fn test_is_prime_order() {
+    for order in [
+        2,
+        3,
+        17,
+        101,
+        0xffff_ffff_0000_0001,
+        18_446_744_073_709_551_557,
+    ] {
+        assert!(Field::new(order).is_prime_order(), "{}", order);
+    }
+    // 3_215_031_751 = 151 * 751 * 28351 is a strong pseudoprime to bases 2, 3, 5 and 7
+    for order in [0, 1, 4, 15, 561, 3_215_031_751, u64::MAX] {
+        assert!(!Field::new(order).is_prime_order(), "{}", order);
+    }
+}
+
+#[test]
+fn test_large_prime_does_not_overflow() {
+    // 2^64 - 2^32 + 1
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let a = field.element(field.order - 1);
+
+    assert_eq!(a + a, field.order - 2);
+    assert_eq!(a * a, 1);
+    assert_eq!(a - field.one() - a, field.order - 1);
+    assert_eq!(a * a.inverse().unwrap(), 1);
 }
@@ -1,311 +1,462 @@
-use crate::math::field::Field;
-
-pub fn get_matrix_determinant_4x4(m: &Vec<Vec<u32>>, field: &Field) -> u32 {
-    let section1 = (m[0][0] * m[1][1] * m[2][2] * m[3][3]
-        + m[0][0] * m[1][2] * m[2][3] * m[3][1]
-        + m[0][0] * m[1][3] * m[2][1] * m[3][2])
-        % field.order;
-
-    let section2 = (m[0][0] * m[1][3] * m[2][2] * m[3][1]
-        + m[0][0] * m[1][2] * m[2][1] * m[3][3]
-        + m[0][0] * m[1][1] * m[2][3] * m[3][2]
-        + m[0][1] * m[1][0] * m[2][2] * m[3][3]
-        + m[0][2] * m[1][0] * m[2][3] * m[3][1]
-        + m[0][3] * m[1][0] * m[2][1] * m[3][2])
-        % field.order;
-
-    let section3 = (m[0][3] * m[1][0] * m[2][2] * m[3][1]
-        + m[0][2] * m[1][0] * m[2][1] * m[3][3]
-        + m[0][1] * m[1][0] * m[2][3] * m[3][2]
-        + m[0][1] * m[1][2] * m[2][0] * m[3][3]
-        + m[0][2] * m[1][3] * m[2][0] * m[3][1]
-        + m[0][3] * m[1][1] * m[2][0] * m[2][3])
-        % field.order;
-
-    let section4 = (m[0][3] * m[1][2] * m[2][0] * m[3][1]
-        + m[0][2] * m[1][1] * m[2][0] * m[3][3]
-        + m[0][1] * m[1][3] * m[2][0] * m[3][2]
-        + m[0][1] * m[1][2] * m[2][3] * m[3][0]
-        + m[0][2] * m[1][3] * m[2][1] * m[3][0]
-        + m[0][3] * m[1][1] * m[2][2] * m[3][0])
-        % field.order;
-
-    let section5 = (m[0][3] * m[1][2] * m[2][1] * m[3][0]
-        + m[0][2] * m[1][1] * m[2][3] * m[3][0]
-        + m[0][1] * m[1][3] * m[2][2] * m[3][0])
-        % field.order;
-
-    field.add(
-        section1,
-        field.add(
-            section3,
-            field.subtract(section5, field.add(section2, section4)),
-        ),
-    )
+use crate::math::field::{Field, FieldElement};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    // rows of different lengths were passed to Matrix::new
+    RaggedRows,
+    NotSquare {
+        rows: usize,
+        cols: usize,
+    },
+    DimensionMismatch {
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    Singular,
+    // Ax = b has no solution
+    Inconsistent,
+}
+
+// A dense rows x cols matrix over a prime field, stored row-major
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub entries: Vec<Vec<FieldElement>>,
+    pub field: Field,
 }
 
-pub fn get_inverse_matrix_4x4(m: &Vec<Vec<u32>>, field: &Field) -> Vec<Vec<u32>> {
-    let determinant = get_matrix_determinant_4x4(m, field);
-    let determinant_inverse = field.multiplicative_inverse(determinant);
-    let mut adj = get_adjugate_matrix_4x4(m, field);
+// Output of Gaussian elimination: the reduced row echelon form, the column of each
+// pivot, and the factor the determinant picked up from row swaps and scaling
+struct RowReduction {
+    reduced: Matrix,
+    pivot_cols: Vec<usize>,
+    determinant_factor: FieldElement,
+}
 
-    for i in 0..adj.len() {
-        for k in 0..adj[i].len() {
-            adj[i][k] = field.multiply(adj[i][k], determinant_inverse);
+impl Matrix {
+    pub fn new(entries: Vec<Vec<FieldElement>>, field: Field) -> Result<Matrix, MatrixError> {
+        let rows = entries.len();
+        let cols = entries.first().map_or(0, |row| row.len());
+        if entries.iter().any(|row| row.len() != cols) {
+            return Err(MatrixError::RaggedRows);
         }
+
+        Ok(Matrix {
+            rows,
+            cols,
+            entries,
+            field,
+        })
     }
 
-    adj
-}
+    pub fn from_values(values: &[Vec<u64>], field: Field) -> Result<Matrix, MatrixError> {
+        Matrix::new(
+            values.iter().map(|row| field.elements(row)).collect(),
+            field,
+        )
+    }
 
-pub fn matrix_multiply_4x4_1x4(m: &Vec<Vec<u32>>, v: &Vec<u32>, field: &Field) -> Vec<u32> {
-    let mut res = Vec::new();
+    pub fn zero(rows: usize, cols: usize, field: Field) -> Matrix {
+        Matrix {
+            rows,
+            cols,
+            entries: vec![vec![field.zero(); cols]; rows],
+            field,
+        }
+    }
 
-    for i in 0..v.len() {
-        let mut ele = 0;
-        for k in 0..m.len() {
-            ele = field.add(ele, field.multiply(v[k], m[i][k]));
+    pub fn identity(size: usize, field: Field) -> Matrix {
+        let mut res = Matrix::zero(size, size, field);
+        for (i, row) in res.entries.iter_mut().enumerate() {
+            row[i] = field.one();
         }
-        res.push(ele);
+
+        res
     }
-    res
-}
 
-fn get_adjugate_matrix_4x4(m: &Vec<Vec<u32>>, field: &Field) -> Vec<Vec<u32>> {
-    let mut adj: Vec<Vec<u32>> = vec![vec![0; 4]; 4];
+    pub fn transpose(&self) -> Matrix {
+        let mut res = Matrix::zero(self.cols, self.rows, self.field);
+        for (i, row) in self.entries.iter().enumerate() {
+            for (j, ele) in row.iter().enumerate() {
+                res.entries[j][i] = *ele;
+            }
+        }
 
-    adj[0][0] =
-        (m[1][1] * m[2][2] * m[3][3] + m[1][2] * m[2][3] * m[3][1] + m[1][3] * m[2][1] * m[3][2])
-            % field.order;
-    adj[0][0] = field.subtract(
-        adj[0][0],
-        (m[1][3] * m[2][2] * m[3][1] + m[1][2] * m[2][1] * m[3][3] + m[1][1] * m[2][3] * m[3][2])
-            % field.order,
-    );
+        res
+    }
 
-    adj[0][1] =
-        (m[0][3] * m[2][2] * m[3][1] + m[0][2] * m[2][1] * m[3][3] + m[0][1] * m[2][3] * m[3][2])
-            % field.order;
-    adj[0][1] = field.subtract(
-        adj[0][1],
-        (m[0][1] * m[2][2] * m[3][3] + m[0][2] * m[2][3] * m[3][1] + m[0][3] * m[2][1] * m[3][2])
-            % field.order,
-    );
+    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (other.rows, other.cols),
+            });
+        }
 
-    adj[0][2] =
-        (m[0][1] * m[1][2] * m[3][3] + m[0][2] * m[1][3] * m[3][1] + m[0][3] * m[1][1] * m[3][2])
-            % field.order;
-    adj[0][2] = field.subtract(
-        adj[0][2],
-        (m[0][3] * m[1][2] * m[3][1] + m[0][2] * m[1][1] * m[3][3] + m[0][1] * m[1][3] * m[3][2])
-            % field.order,
-    );
+        let mut res = Matrix::zero(self.rows, other.cols, self.field);
+        for (res_row, row) in res.entries.iter_mut().zip(self.entries.iter()) {
+            for (ele, other_row) in row.iter().zip(other.entries.iter()) {
+                for (res_ele, other_ele) in res_row.iter_mut().zip(other_row.iter()) {
+                    *res_ele += *ele * *other_ele;
+                }
+            }
+        }
 
-    adj[0][3] =
-        (m[0][3] * m[1][2] * m[2][1] + m[0][2] * m[1][1] * m[2][3] + m[0][1] * m[1][3] * m[2][2])
-            % field.order;
-    adj[0][3] = field.subtract(
-        adj[0][3],
-        (m[0][1] * m[1][2] * m[2][3] + m[0][2] * m[1][3] * m[2][1] + m[0][3] * m[1][1] * m[2][2])
-            % field.order,
-    );
+        Ok(res)
+    }
 
-    adj[1][0] =
-        (m[1][3] * m[2][2] * m[3][0] + m[1][2] * m[2][0] * m[3][3] + m[1][0] * m[2][3] * m[3][2])
-            % field.order;
-    adj[1][0] = field.subtract(
-        adj[1][0],
-        (m[1][0] * m[2][2] * m[3][3] + m[1][2] * m[2][3] * m[3][0] + m[1][3] * m[2][0] * m[3][2])
-            % field.order,
-    );
+    pub fn multiply_vector(&self, v: &[FieldElement]) -> Result<Vec<FieldElement>, MatrixError> {
+        if self.cols != v.len() {
+            return Err(MatrixError::DimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (v.len(), 1),
+            });
+        }
 
-    adj[1][1] =
-        (m[0][0] * m[2][2] * m[3][3] + m[0][2] * m[2][3] * m[3][0] + m[0][3] * m[2][0] * m[3][2])
-            % field.order;
-    adj[1][1] = field.subtract(
-        adj[1][1],
-        (m[0][3] * m[2][2] * m[3][0] + m[0][2] * m[2][0] * m[3][3] + m[0][0] * m[2][3] * m[3][2])
-            % field.order,
-    );
+        Ok(self
+            .entries
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(v.iter())
+                    .fold(self.field.zero(), |acc, (a, b)| acc + *a * *b)
+            })
+            .collect())
+    }
 
-    adj[1][2] =
-        (m[0][3] * m[1][2] * m[3][0] + m[0][2] * m[1][0] * m[3][3] + m[0][0] * m[1][3] * m[3][2])
-            % field.order;
-    adj[1][2] = field.subtract(
-        adj[1][2],
-        (m[0][0] * m[1][2] * m[3][3] + m[0][2] * m[1][3] * m[3][0] + m[0][3] * m[1][0] * m[3][2])
-            % field.order,
-    );
+    // Reduced row echelon form
+    pub fn row_reduce(&self) -> Matrix {
+        self.gaussian_elimination().reduced
+    }
 
-    adj[1][3] =
-        (m[0][0] * m[1][2] * m[2][3] + m[0][2] * m[1][3] * m[2][0] + m[0][3] * m[1][0] * m[2][2])
-            % field.order;
-    adj[1][3] = field.subtract(
-        adj[1][3],
-        (m[0][3] * m[1][2] * m[2][0] + m[0][2] * m[1][0] * m[2][3] + m[0][0] * m[1][3] * m[2][2])
-            % field.order,
-    );
+    pub fn rank(&self) -> usize {
+        self.gaussian_elimination().pivot_cols.len()
+    }
 
-    adj[2][0] =
-        (m[1][0] * m[2][1] * m[3][3] + m[1][1] * m[2][3] * m[3][0] + m[1][3] * m[2][0] * m[3][1])
-            % field.order;
-    adj[2][0] = field.subtract(
-        adj[2][0],
-        (m[1][3] * m[2][1] * m[3][0] + m[1][1] * m[2][0] * m[3][3] + m[1][0] * m[2][3] * m[3][1])
-            % field.order,
-    );
+    pub fn determinant(&self) -> Result<FieldElement, MatrixError> {
+        self.check_square()?;
 
-    adj[2][1] =
-        (m[0][3] * m[2][1] * m[3][0] + m[0][1] * m[2][0] * m[3][3] + m[0][0] * m[2][3] * m[3][1])
-            % field.order;
-    adj[2][1] = field.subtract(
-        adj[2][1],
-        (m[0][0] * m[2][1] * m[3][3] + m[0][1] * m[2][3] * m[3][0] + m[0][3] * m[2][0] * m[3][1])
-            % field.order,
-    );
+        let reduction = self.gaussian_elimination();
+        if reduction.pivot_cols.len() < self.rows {
+            return Ok(self.field.zero());
+        }
 
-    adj[2][2] =
-        (m[0][0] * m[1][1] * m[3][3] + m[0][1] * m[1][3] * m[3][0] + m[0][3] * m[1][0] * m[3][1])
-            % field.order;
-    adj[2][2] = field.subtract(
-        adj[2][2],
-        (m[0][3] * m[1][1] * m[3][0] + m[0][1] * m[1][0] * m[3][3] + m[0][0] * m[1][3] * m[3][1])
-            % field.order,
-    );
+        // the reduced form is the identity, so det(A) is the inverse of the product
+        // of everything elimination multiplied A's determinant by
+        Ok(reduction
+            .determinant_factor
+            .inverse()
+            .expect("row operations only scale by non-zero factors"))
+    }
 
-    adj[2][3] =
-        (m[0][3] * m[1][1] * m[2][0] + m[0][1] * m[1][0] * m[2][3] + m[0][0] * m[1][3] * m[2][1])
-            % field.order;
-    adj[2][3] = field.subtract(
-        adj[2][3],
-        (m[0][0] * m[1][1] * m[2][3] + m[0][1] * m[1][3] * m[2][0] + m[0][3] * m[1][0] * m[2][1])
-            % field.order,
-    );
+    pub fn inverse(&self) -> Result<Matrix, MatrixError> {
+        self.check_square()?;
+
+        // reduce [A | I] to [I | A^-1]
+        let augmented = Matrix {
+            rows: self.rows,
+            cols: 2 * self.cols,
+            entries: self
+                .entries
+                .iter()
+                .zip(Matrix::identity(self.rows, self.field).entries)
+                .map(|(row, identity_row)| [row.clone(), identity_row].concat())
+                .collect(),
+            field: self.field,
+        };
+
+        let reduction = augmented.gaussian_elimination();
+        // A is invertible iff every pivot lands in its own columns
+        let pivots_in_a = reduction.pivot_cols.iter().filter(|col| **col < self.cols);
+        if pivots_in_a.count() < self.rows {
+            return Err(MatrixError::Singular);
+        }
 
-    adj[3][0] =
-        (m[1][2] * m[2][1] * m[3][0] + m[1][1] * m[2][0] * m[3][2] + m[1][0] * m[2][2] * m[3][1])
-            % field.order;
-    adj[3][0] = field.subtract(
-        adj[3][0],
-        (m[1][0] * m[2][1] * m[3][2] + m[1][1] * m[2][2] * m[3][0] + m[1][2] * m[2][0] * m[3][1])
-            % field.order,
-    );
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            entries: reduction
+                .reduced
+                .entries
+                .into_iter()
+                .map(|row| row[self.cols..].to_vec())
+                .collect(),
+            field: self.field,
+        })
+    }
 
-    adj[3][1] =
-        (m[0][0] * m[2][1] * m[3][2] + m[0][1] * m[2][2] * m[3][0] + m[0][2] * m[2][0] * m[3][1])
-            % field.order;
-    adj[3][1] = field.subtract(
-        adj[3][1],
-        (m[0][2] * m[2][1] * m[3][0] + m[0][1] * m[2][0] * m[3][2] + m[0][0] * m[2][2] * m[3][1])
-            % field.order,
-    );
+    // Returns a solution of Ax = b. If there are many, free variables are set to zero.
+    pub fn solve(&self, b: &[FieldElement]) -> Result<Vec<FieldElement>, MatrixError> {
+        if self.rows != b.len() {
+            return Err(MatrixError::DimensionMismatch {
+                left: (self.rows, self.cols),
+                right: (b.len(), 1),
+            });
+        }
 
-    adj[3][2] =
-        (m[0][2] * m[1][1] * m[3][0] + m[0][1] * m[1][0] * m[3][2] + m[0][0] * m[1][2] * m[3][1])
-            % field.order;
-    adj[3][2] = field.subtract(
-        adj[3][2],
-        (m[0][0] * m[1][1] * m[3][2] + m[0][1] * m[1][2] * m[3][0] + m[0][2] * m[1][0] * m[3][1])
-            % field.order,
-    );
+        let augmented = Matrix {
+            rows: self.rows,
+            cols: self.cols + 1,
+            entries: self
+                .entries
+                .iter()
+                .zip(b.iter())
+                .map(|(row, b_i)| [row.as_slice(), &[*b_i]].concat())
+                .collect(),
+            field: self.field,
+        };
+
+        let reduction = augmented.gaussian_elimination();
+        if reduction.pivot_cols.contains(&self.cols) {
+            return Err(MatrixError::Inconsistent);
+        }
 
-    adj[3][3] =
-        (m[0][0] * m[1][1] * m[2][2] + m[0][1] * m[1][2] * m[2][0] + m[0][2] * m[1][0] * m[2][1])
-            % field.order;
-    adj[3][3] = field.subtract(
-        adj[3][3],
-        (m[0][2] * m[1][1] * m[2][0] + m[0][1] * m[1][0] * m[2][2] + m[0][0] * m[1][2] * m[2][1])
-            % field.order,
-    );
+        let mut x = vec![self.field.zero(); self.cols];
+        for (row, pivot_col) in reduction.pivot_cols.iter().enumerate() {
+            x[*pivot_col] = reduction.reduced.entries[row][self.cols];
+        }
+
+        Ok(x)
+    }
+
+    fn check_square(&self) -> Result<(), MatrixError> {
+        if self.rows != self.cols {
+            return Err(MatrixError::NotSquare {
+                rows: self.rows,
+                cols: self.cols,
+            });
+        }
 
-    adj
+        Ok(())
+    }
+
+    fn gaussian_elimination(&self) -> RowReduction {
+        let mut m = self.entries.clone();
+        let mut pivot_cols = Vec::new();
+        let mut determinant_factor = self.field.one();
+
+        let mut pivot_row = 0;
+        for col in 0..self.cols {
+            if pivot_row == self.rows {
+                break;
+            }
+
+            let Some(found) = (pivot_row..self.rows).find(|row| !m[*row][col].is_zero()) else {
+                continue;
+            };
+            if found != pivot_row {
+                m.swap(found, pivot_row);
+                determinant_factor = -determinant_factor;
+            }
+
+            let pivot_inv = m[pivot_row][col].inverse().unwrap();
+            for ele in m[pivot_row].iter_mut() {
+                *ele *= pivot_inv;
+            }
+            determinant_factor *= pivot_inv;
+
+            let pivot = m[pivot_row].clone();
+            for (row, entries) in m.iter_mut().enumerate() {
+                let factor = entries[col];
+                if row == pivot_row || factor.is_zero() {
+                    continue;
+                }
+                for (ele, pivot_ele) in entries.iter_mut().zip(pivot.iter()) {
+                    *ele -= factor * *pivot_ele;
+                }
+            }
+
+            pivot_cols.push(col);
+            pivot_row += 1;
+        }
+
+        RowReduction {
+            reduced: Matrix {
+                rows: self.rows,
+                cols: self.cols,
+                entries: m,
+                field: self.field,
+            },
+            pivot_cols,
+            determinant_factor,
+        }
+    }
 }
 
 #[test]
 fn test_determinant() {
     let field = Field { order: 17 };
 
-    let m: Vec<Vec<u32>> = vec![
-        vec![1, 2, 6, 6],
-        vec![4, 7, 3, 2],
-        vec![0, 0, 0, 0],
-        vec![1, 2, 2, 9],
-    ];
-    assert_eq!(get_matrix_determinant_4x4(&m, &field), 0);
-    let m: Vec<Vec<u32>> = vec![
-        vec![4, 3, 2, 2],
-        vec![0, 1, 14, 3],
-        vec![0, 16, 3, 3],
-        vec![0, 3, 1, 1],
-    ];
-    assert_eq!(get_matrix_determinant_4x4(&m, &field), 15);
-    let m: Vec<Vec<u32>> = vec![
-        vec![1, 1, 1, 16],
-        vec![1, 1, 16, 1],
-        vec![1, 16, 1, 1],
-        vec![16, 1, 1, 1],
-    ];
-    assert_eq!(get_matrix_determinant_4x4(&m, &field), 1);
+    let m = Matrix::from_values(
+        &[
+            vec![1, 2, 6, 6],
+            vec![4, 7, 3, 2],
+            vec![0, 0, 0, 0],
+            vec![1, 2, 2, 9],
+        ],
+        field,
+    )
+    .unwrap();
+    assert_eq!(m.determinant().unwrap(), 0);
+    let m = Matrix::from_values(
+        &[
+            vec![4, 3, 2, 2],
+            vec![0, 1, 14, 3],
+            vec![0, 16, 3, 3],
+            vec![0, 3, 1, 1],
+        ],
+        field,
+    )
+    .unwrap();
+    assert_eq!(m.determinant().unwrap(), 15);
+    let m = Matrix::from_values(
+        &[
+            vec![1, 1, 1, 16],
+            vec![1, 1, 16, 1],
+            vec![1, 16, 1, 1],
+            vec![16, 1, 1, 1],
+        ],
+        field,
+    )
+    .unwrap();
+    assert_eq!(m.determinant().unwrap(), 1);
+
+    let m = Matrix::from_values(&[vec![2, 3], vec![5, 7]], field).unwrap();
+    assert_eq!(m.determinant().unwrap(), 16);
+
+    let not_square = Matrix::from_values(&[vec![1, 2, 3]], field).unwrap();
+    assert_eq!(
+        not_square.determinant(),
+        Err(MatrixError::NotSquare { rows: 1, cols: 3 })
+    );
 }
 
 #[test]
 fn test_inverse() {
     let field = Field { order: 17 };
-    let m: Vec<Vec<u32>> = vec![
-        vec![1, 1, 1, 16],
-        vec![1, 1, 16, 1],
-        vec![1, 16, 1, 1],
-        vec![16, 1, 1, 1],
-    ];
-    let adj = get_adjugate_matrix_4x4(&m, &field);
-    let expected_adj = vec![
-        vec![13, 13, 13, 4],
-        vec![13, 13, 4, 13],
-        vec![13, 4, 13, 13],
-        vec![4, 13, 13, 13],
-    ];
-
-    for i in 0..adj.len() {
-        for k in 0..adj[i].len() {
-            assert_eq!(adj[i][k], expected_adj[i][k]);
-        }
-    }
 
-    let m2: Vec<Vec<u32>> = vec![
-        vec![1, 1, 1, 1],
-        vec![1, 4, 16, 13],
-        vec![1, 16, 1, 16],
-        vec![1, 13, 16, 4],
-    ];
-    let expected_inverse: Vec<Vec<u32>> = vec![
-        vec![13, 13, 13, 13],
-        vec![13, 16, 4, 1],
-        vec![13, 4, 13, 4],
-        vec![13, 1, 4, 16],
-    ];
-    let actual_inverse: Vec<Vec<u32>> = get_inverse_matrix_4x4(&m2, &field);
-
-    for i in 0..actual_inverse.len() {
-        for k in 0..actual_inverse[i].len() {
-            assert_eq!(actual_inverse[i][k], expected_inverse[i][k]);
-        }
-    }
+    // determinant 1, so the inverse is the adjugate
+    let m = Matrix::from_values(
+        &[
+            vec![1, 1, 1, 16],
+            vec![1, 1, 16, 1],
+            vec![1, 16, 1, 1],
+            vec![16, 1, 1, 1],
+        ],
+        field,
+    )
+    .unwrap();
+    let expected_adj = Matrix::from_values(
+        &[
+            vec![13, 13, 13, 4],
+            vec![13, 13, 4, 13],
+            vec![13, 4, 13, 13],
+            vec![4, 13, 13, 13],
+        ],
+        field,
+    )
+    .unwrap();
+    assert_eq!(m.inverse().unwrap(), expected_adj);
+
+    let m2 = Matrix::from_values(
+        &[
+            vec![1, 1, 1, 1],
+            vec![1, 4, 16, 13],
+            vec![1, 16, 1, 16],
+            vec![1, 13, 16, 4],
+        ],
+        field,
+    )
+    .unwrap();
+    let expected_inverse = Matrix::from_values(
+        &[
+            vec![13, 13, 13, 13],
+            vec![13, 16, 4, 1],
+            vec![13, 4, 13, 4],
+            vec![13, 1, 4, 16],
+        ],
+        field,
+    )
+    .unwrap();
+    let actual_inverse = m2.inverse().unwrap();
+    assert_eq!(actual_inverse, expected_inverse);
+    assert_eq!(
+        m2.multiply(&actual_inverse).unwrap(),
+        Matrix::identity(4, field)
+    );
+
+    let singular = Matrix::from_values(&[vec![1, 2], vec![2, 4]], field).unwrap();
+    assert_eq!(singular.inverse(), Err(MatrixError::Singular));
 }
 
 #[test]
 fn test_matrix_mul() {
     let field = Field { order: 17 };
-    let m: Vec<Vec<u32>> = vec![
-        vec![13, 13, 13, 13],
-        vec![13, 16, 4, 1],
-        vec![13, 4, 13, 4],
-        vec![13, 1, 4, 16],
-    ];
-    let v: Vec<u32> = vec![3, 4, 5, 9];
-    let expected_res: Vec<u32> = vec![1, 13, 3, 3];
-
-    let actual_res = matrix_multiply_4x4_1x4(&m, &v, &field);
-    for i in 0..actual_res.len() {
-        assert_eq!(actual_res[i], expected_res[i]);
-    }
+    let m = Matrix::from_values(
+        &[
+            vec![13, 13, 13, 13],
+            vec![13, 16, 4, 1],
+            vec![13, 4, 13, 4],
+            vec![13, 1, 4, 16],
+        ],
+        field,
+    )
+    .unwrap();
+    let v = field.elements(&[3, 4, 5, 9]);
+    let expected_res = vec![1, 13, 3, 3];
+    assert_eq!(m.multiply_vector(&v).unwrap(), expected_res);
+
+    let a = Matrix::from_values(&[vec![1, 2, 3], vec![4, 5, 6]], field).unwrap();
+    let b = a.transpose();
+    assert_eq!((b.rows, b.cols), (3, 2));
+    assert_eq!(
+        a.multiply(&b).unwrap(),
+        Matrix::from_values(&[vec![14, 15], vec![15, 9]], field).unwrap()
+    );
+    assert_eq!(
+        a.multiply(&a),
+        Err(MatrixError::DimensionMismatch {
+            left: (2, 3),
+            right: (2, 3)
+        })
+    );
+    assert_eq!(
+        Matrix::from_values(&[vec![1, 2], vec![3]], field),
+        Err(MatrixError::RaggedRows)
+    );
+}
+
+#[test]
+fn test_rank_and_solve() {
+    let field = Field { order: 17 };
+
+    let m = Matrix::from_values(
+        &[
+            vec![1, 1, 1, 1],
+            vec![1, 4, 16, 13],
+            vec![1, 16, 1, 16],
+            vec![1, 13, 16, 4],
+        ],
+        field,
+    )
+    .unwrap();
+    assert_eq!(m.rank(), 4);
+    // interpolating the left wire values recovers the article's polynomial
+    let x = m.solve(&field.elements(&[3, 4, 5, 9])).unwrap();
+    assert_eq!(x, vec![1, 13, 3, 3]);
+
+    let m = Matrix::from_values(&[vec![1, 2, 3], vec![2, 4, 6], vec![1, 0, 1]], field).unwrap();
+    assert_eq!(m.rank(), 2);
+    assert_eq!(
+        m.row_reduce(),
+        Matrix::from_values(&[vec![1, 0, 1], vec![0, 1, 1], vec![0, 0, 0]], field).unwrap()
+    );
+    let b = field.elements(&[5, 10, 3]);
+    let x = m.solve(&b).unwrap();
+    assert_eq!(m.multiply_vector(&x).unwrap(), b);
+    assert_eq!(
+        m.solve(&field.elements(&[5, 11, 3])),
+        Err(MatrixError::Inconsistent)
+    );
 }
@@ -1,18 +1,27 @@
+#[cfg(test)]
 use crate::constants;
 use crate::math::complex_scalar::ComplexScalar;
-use crate::{CurvePoint, ExtensionCurvePoint, Field, ECC};
+#[cfg(test)]
+use crate::Field;
+use crate::{CurvePoint, ExtensionCurvePoint, ECC};
 
 pub struct Pairing {
-    pub r: u32,
+    pub r: u64,
     pub ecc: ECC,
 }
 
 impl Pairing {
     pub fn get_base_pairing(&self, q: &ExtensionCurvePoint, p: &CurvePoint) -> ComplexScalar {
         let f17 = self.get_f_17(q, p);
-        let exponent = (u32::pow(self.ecc.field.order, 2) - 1) / (self.r);
+        // (p^2 - 1) / r, which needs more than 64 bits once p does not fit in 32
+        let order = self.ecc.field.order as u128;
+        let exponent = (order * order - 1) / self.r as u128;
 
-        ComplexScalar::exponent(&self.ecc.field, &f17, exponent)
+        ComplexScalar::exponent_limbs(
+            &self.ecc.field,
+            &f17,
+            &[exponent as u64, (exponent >> 64) as u64],
+        )
     }
 
     fn get_f_17(&self, q: &ExtensionCurvePoint, p: &CurvePoint) -> ComplexScalar {
@@ -1,254 +1,461 @@
-use crate::math::field::Field;
-use crate::math::matrix;
+use crate::math::field::{Field, FieldElement};
+use crate::math::roots_of_unity::EvaluationDomain;
+use std::ops::{Add, Mul, Neg, Sub};
 
+// products with at most this many coefficients are multiplied schoolbook
+const NTT_THRESHOLD: usize = 64;
+
+// Coefficients are lowest degree first and always in canonical form: no trailing
+// zeros, and the zero polynomial is [0]. Every constructor goes through new() to
+// keep it that way, so degree() is always coefficients.len() - 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Polynomial {
-    pub degree: u32,
-    pub coefficients: Vec<u32>,
+    coefficients: Vec<FieldElement>,
     pub field: Field,
 }
 
 #[derive(Debug)]
 pub struct Point {
-    pub x: u32,
-    pub y: u32,
+    pub x: FieldElement,
+    pub y: FieldElement,
 }
 
 impl Default for Polynomial {
     fn default() -> Polynomial {
         Polynomial {
-            degree: 0,
-            coefficients: Vec::new(),
+            coefficients: vec![FieldElement::default()],
             field: Field { order: 0 },
         }
     }
 }
 
 impl Polynomial {
-    pub fn eval(&self, x: u32) -> u32 {
-        let mut res = self.coefficients[0];
-        for i in 1..(self.degree + 1) {
-            let x_i = self.field.exponent(x, i);
-            res = self
-                .field
-                .add(res, self.field.multiply(self.coefficients[i as usize], x_i));
+    pub fn new(mut coefficients: Vec<FieldElement>, field: Field) -> Polynomial {
+        while coefficients.len() > 1 && coefficients[coefficients.len() - 1].is_zero() {
+            coefficients.pop();
+        }
+        if coefficients.is_empty() {
+            coefficients.push(field.zero());
         }
 
-        res
+        Polynomial {
+            coefficients,
+            field,
+        }
     }
 
-    pub fn scalar_multiply(&self, scalar: u32) -> Polynomial {
-        let new_coefficients = self
-            .coefficients
-            .iter()
-            .map(|coefficient| self.field.multiply(scalar, *coefficient))
-            .collect();
+    pub fn zero(field: Field) -> Polynomial {
+        Polynomial::from_scalar(field.zero(), field)
+    }
 
-        Polynomial::remove_zeros(&Polynomial {
-            degree: self.degree,
-            coefficients: new_coefficients,
-            field: self.field.clone(),
-        })
+    pub fn from_scalar(scalar: FieldElement, field: Field) -> Polynomial {
+        Polynomial::new(vec![scalar], field)
     }
 
-    // 0*0, 1*0 0*1, 2*0 0*2 1*1, ...
-    // TODO: impl FFT
-    pub fn poly_multiply(poly1: &Polynomial, poly2: &Polynomial) -> Polynomial {
-        let new_degree = poly1.degree + poly2.degree;
-        let mut new_coefficients = vec![0; (new_degree + 1) as usize];
-
-        for i in 0..=new_degree {
-            for k in 0..=poly1.degree {
-                for j in 0..=poly2.degree {
-                    if k + j == i {
-                        new_coefficients[i as usize] = poly1.field.add(
-                            new_coefficients[i as usize],
-                            poly1.field.multiply(
-                                poly1.coefficients[k as usize],
-                                poly2.coefficients[j as usize],
-                            ),
-                        );
-                    }
-                }
+    // X - root
+    pub fn linear(root: FieldElement, field: Field) -> Polynomial {
+        Polynomial::new(vec![-root, field.one()], field)
+    }
+
+    pub fn coefficients(&self) -> &[FieldElement] {
+        &self.coefficients
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.len() == 1 && self.coefficients[0].is_zero()
+    }
+
+    pub fn leading_coefficient(&self) -> FieldElement {
+        self.coefficients[self.degree()]
+    }
+
+    // Horner's rule
+    pub fn eval(&self, x: FieldElement) -> FieldElement {
+        let mut res = self.field.zero();
+        for coefficient in self.coefficients.iter().rev() {
+            res = res * x + *coefficient;
+        }
+
+        res
+    }
+
+    // Long division, returns (quotient, remainder). Panics on division by zero.
+    pub fn divide(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        assert!(!divisor.is_zero(), "division by the zero polynomial");
+        if self.degree() < divisor.degree() {
+            return (Polynomial::zero(self.field), self.clone());
+        }
+
+        let lead_inv = divisor.leading_coefficient().inverse().unwrap();
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![self.field.zero(); self.degree() - divisor.degree() + 1];
+
+        for i in (0..quotient.len()).rev() {
+            let t = remainder[i + divisor.degree()] * lead_inv;
+            quotient[i] = t;
+            for (j, d) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] -= t * *d;
             }
         }
 
-        Polynomial::remove_zeros(&Polynomial {
-            degree: new_degree,
-            coefficients: new_coefficients,
-            field: poly1.field.clone(),
-        })
+        (
+            Polynomial::new(quotient, self.field),
+            Polynomial::new(remainder, self.field),
+        )
     }
 
-    pub fn poly_add(poly1: &Polynomial, poly2: &Polynomial) -> Polynomial {
-        let mut large = poly1;
-        let mut small = poly2;
-        if poly1.degree < poly2.degree {
-            large = poly2;
-            small = poly1;
+    // Synthetic division by X - z, returns (quotient, remainder). The remainder is
+    // p(z), so (p - p(z)) / (X - z) is just the quotient: a KZG opening witness.
+    pub fn divide_by_linear(&self, z: FieldElement) -> (Polynomial, FieldElement) {
+        if self.degree() == 0 {
+            return (Polynomial::zero(self.field), self.coefficients[0]);
         }
 
-        let mut res = large.copy();
-        for i in 0..small.coefficients.len() {
-            res.coefficients[i] = poly1
-                .field
-                .add(large.coefficients[i], small.coefficients[i]);
+        let mut quotient = vec![self.field.zero(); self.degree()];
+        let mut carry = self.field.zero();
+        for i in (1..=self.degree()).rev() {
+            carry = self.coefficients[i] + carry * z;
+            quotient[i - 1] = carry;
         }
+        let remainder = self.coefficients[0] + carry * z;
 
-        Polynomial::remove_zeros(&res)
+        (Polynomial::new(quotient, self.field), remainder)
     }
 
-    pub fn poly_subtract(poly1: &Polynomial, poly2: &Polynomial) -> Polynomial {
-        Polynomial::poly_add(poly1, &Polynomial::additive_inverse_poly(poly2))
+    // Division by X^n - 1 in O(deg) using X^n = 1 + (X^n - 1), returns (quotient, remainder)
+    pub fn divide_by_vanishing(&self, n: usize) -> (Polynomial, Polynomial) {
+        assert!(n > 0, "X^0 - 1 is the zero polynomial");
+        if self.degree() < n {
+            return (Polynomial::zero(self.field), self.clone());
+        }
+
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![self.field.zero(); self.degree() - n + 1];
+        for i in (n..=self.degree()).rev() {
+            // the X^i term becomes q_(i-n) * X^(i-n) * (X^n - 1), leaving X^(i-n)
+            let t = remainder[i];
+            quotient[i - n] = t;
+            remainder[i] = self.field.zero();
+            remainder[i - n] += t;
+        }
+
+        (
+            Polynomial::new(quotient, self.field),
+            Polynomial::new(remainder, self.field),
+        )
+    }
+
+    pub fn derivative(&self) -> Polynomial {
+        let coefficients = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, coefficient)| *coefficient * self.field.element(i as u64))
+            .collect();
+
+        Polynomial::new(coefficients, self.field)
     }
 
-    pub fn additive_inverse_poly(poly: &Polynomial) -> Polynomial {
-        let mut new_coefficients = vec![0; (poly.degree + 1) as usize];
-        for i in 0..=poly.degree {
-            new_coefficients[i as usize] =
-                poly.field.additive_inverse(poly.coefficients[i as usize]);
+    // p(q(X)), by Horner's rule over polynomials
+    pub fn compose(&self, other: &Polynomial) -> Polynomial {
+        let mut res = Polynomial::zero(self.field);
+        for coefficient in self.coefficients.iter().rev() {
+            res = res * other + *coefficient;
         }
 
-        Polynomial {
-            degree: poly.degree,
-            coefficients: new_coefficients,
-            field: poly.field.clone(),
+        res
+    }
+
+    // X^k * p(X)
+    pub fn shift(&self, k: usize) -> Polynomial {
+        if self.is_zero() {
+            return self.clone();
         }
+
+        let mut coefficients = vec![self.field.zero(); k];
+        coefficients.extend_from_slice(&self.coefficients);
+        Polynomial::new(coefficients, self.field)
     }
 
-    pub fn poly_divide(poly1: &Polynomial, poly2: &Polynomial) -> (Polynomial, Polynomial) {
-        //function n / d:
-        // 2      require d ≠ 0
-        // 3      (q, r) ← (0, n)            # At each step n = d × q + r
-        // 4      while r ≠ 0 AND degree(r) ≥ degree(d):
-        // 5         t ← lead(r)/lead(d)     # Divide the leading terms
-        // 6         (q, r) ← (q + t, r - (t * d))
-        // 7      return (q, r)
+    // p(c * X), e.g. z(ωX) for the permutation argument
+    pub fn scale_input(&self, c: FieldElement) -> Polynomial {
+        let mut c_power = self.field.one();
+        let coefficients = self
+            .coefficients
+            .iter()
+            .map(|coefficient| {
+                let res = *coefficient * c_power;
+                c_power *= c;
+                res
+            })
+            .collect();
 
-        let mut q = Polynomial {
-            field: poly1.field.clone(),
-            coefficients: Vec::new(),
-            degree: 0,
-        };
+        Polynomial::new(coefficients, self.field)
+    }
 
-        let mut r = Polynomial {
-            field: poly1.field.clone(),
-            coefficients: poly1.coefficients.clone(),
-            degree: poly1.degree,
+    // Uses an NTT when the field has a large enough power-of-two domain and the
+    // product is big enough for it to pay off, schoolbook otherwise
+    fn multiply(&self, other: &Polynomial) -> Polynomial {
+        let product_len = self.coefficients.len() + other.coefficients.len() - 1;
+
+        let coefficients = if product_len <= NTT_THRESHOLD {
+            Polynomial::schoolbook_multiply(self, other)
+        } else {
+            match EvaluationDomain::new(product_len.next_power_of_two(), self.field) {
+                Ok(domain) => {
+                    let products: Vec<FieldElement> = domain
+                        .ntt(&self.coefficients)
+                        .iter()
+                        .zip(domain.ntt(&other.coefficients).iter())
+                        .map(|(a, b)| *a * *b)
+                        .collect();
+                    let mut coefficients = domain.inverse_ntt(&products);
+                    coefficients.truncate(product_len);
+                    coefficients
+                }
+                Err(_) => Polynomial::schoolbook_multiply(self, other),
+            }
         };
 
-        while (r.coefficients[0] != 0 && r.coefficients.len() != 1) && r.degree >= poly2.degree {
-            let new_coefficient = poly1.field.divide(
-                r.coefficients[r.coefficients.len() - 1],
-                poly2.coefficients[poly2.coefficients.len() - 1],
-            );
-
-            let new_degree = r.degree - poly2.degree;
-            let mut new_coefficient_vec = vec![0; (new_degree + 1) as usize];
-            new_coefficient_vec[new_degree as usize] = new_coefficient;
-
-            let t = Polynomial {
-                degree: new_degree,
-                coefficients: new_coefficient_vec,
-                field: poly1.field.clone(),
-            };
-
-            q = Polynomial::poly_add(&q, &t);
-            r = Polynomial::poly_add(
-                &r,
-                &Polynomial::additive_inverse_poly(&Polynomial::poly_multiply(&t, poly2)),
-            );
+        Polynomial::new(coefficients, self.field)
+    }
+
+    // 0*0, 1*0 0*1, 2*0 0*2 1*1, ...
+    fn schoolbook_multiply(poly1: &Polynomial, poly2: &Polynomial) -> Vec<FieldElement> {
+        let product_len = poly1.coefficients.len() + poly2.coefficients.len() - 1;
+        let mut coefficients = vec![poly1.field.zero(); product_len];
+
+        for (k, a) in poly1.coefficients.iter().enumerate() {
+            for (j, b) in poly2.coefficients.iter().enumerate() {
+                coefficients[k + j] += *a * *b;
+            }
         }
 
-        (q, r)
+        coefficients
     }
 
-    pub fn from_scalar(scalar: u32, field: Field) -> Polynomial {
-        Polynomial {
-            degree: 0,
-            coefficients: vec![scalar],
-            field,
+    fn zip_with(
+        &self,
+        other: &Polynomial,
+        op: impl Fn(FieldElement, FieldElement) -> FieldElement,
+    ) -> Polynomial {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        let coefficients = (0..len)
+            .map(|i| {
+                let a = self
+                    .coefficients
+                    .get(i)
+                    .copied()
+                    .unwrap_or(self.field.zero());
+                let b = other
+                    .coefficients
+                    .get(i)
+                    .copied()
+                    .unwrap_or(self.field.zero());
+                op(a, b)
+            })
+            .collect();
+
+        Polynomial::new(coefficients, self.field)
+    }
+
+    // Interpolates the unique polynomial of degree < n through n points. If the x
+    // values are a roots-of-unity domain in order this is an inverse NTT, otherwise
+    // barycentric Lagrange in O(n^2). Panics if two points share an x-coordinate.
+    pub fn create_poly_from_points(points: Vec<Point>, field: &Field) -> Polynomial {
+        if points.is_empty() {
+            return Polynomial::from_scalar(field.zero(), *field);
         }
+
+        let x_vals: Vec<FieldElement> = points.iter().map(|point| point.x).collect();
+        let y_vals: Vec<FieldElement> = points.iter().map(|point| point.y).collect();
+
+        let coefficients = match EvaluationDomain::new(points.len(), *field) {
+            Ok(domain) if domain.elements == x_vals => domain.inverse_ntt(&y_vals),
+            _ => Polynomial::barycentric_interpolation(&x_vals, &y_vals, field),
+        };
+
+        Polynomial::new(coefficients, *field)
     }
 
-    fn remove_zeros(poly: &Polynomial) -> Polynomial {
-        let mut first_non_zero_index = 0;
-        for i in (0..poly.coefficients.len()).rev() {
-            if poly.coefficients[i] != 0 {
-                first_non_zero_index = i;
-                break;
+    // L(X) = sum_i y_i * w_i * M(X) / (X - x_i), with M(X) = prod_j (X - x_j) and
+    // barycentric weights w_i = 1 / prod_{j != i} (x_i - x_j)
+    fn barycentric_interpolation(
+        x_vals: &[FieldElement],
+        y_vals: &[FieldElement],
+        field: &Field,
+    ) -> Vec<FieldElement> {
+        let n = x_vals.len();
+
+        let mut vanishing = vec![field.zero(); n + 1];
+        vanishing[0] = field.one();
+        for (len, x) in x_vals.iter().enumerate() {
+            // multiply by (X - x), highest coefficient first so nothing is overwritten early
+            for i in (1..=len + 1).rev() {
+                vanishing[i] = vanishing[i - 1] - *x * vanishing[i];
             }
+            vanishing[0] = -*x * vanishing[0];
         }
 
-        Polynomial {
-            degree: first_non_zero_index as u32,
-            coefficients: poly.coefficients[0..=first_non_zero_index].to_vec(),
-            field: poly.field.clone(),
+        let weight_denominators: Vec<FieldElement> = x_vals
+            .iter()
+            .enumerate()
+            .map(|(i, x_i)| {
+                x_vals
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(field.one(), |acc, (_, x_j)| acc * (*x_i - *x_j))
+            })
+            .collect();
+        let weights = field
+            .batch_inverse(&weight_denominators)
+            .expect("interpolation points must have distinct x-coordinates");
+
+        let mut coefficients = vec![field.zero(); n];
+        for ((x_i, y_i), w_i) in x_vals.iter().zip(y_vals.iter()).zip(weights.iter()) {
+            let scale = *y_i * *w_i;
+            if scale.is_zero() {
+                continue;
+            }
+
+            // M(X) / (X - x_i) by synthetic division, from the top coefficient down
+            let mut carry = field.zero();
+            for k in (0..n).rev() {
+                carry = vanishing[k + 1] + carry * *x_i;
+                coefficients[k] += scale * carry;
+            }
         }
+
+        coefficients
     }
 
-    pub fn copy(&self) -> Polynomial {
-        Polynomial {
-            degree: self.degree,
-            coefficients: self.coefficients.clone(),
-            field: self.field.clone(),
+    // L_1, the polynomial that is 1 at roots[0] and 0 at every other root
+    pub fn get_lagrange_1_poly(field: &Field, roots: &[FieldElement]) -> Polynomial {
+        let mut points = Vec::new();
+        for (i, root) in roots.iter().enumerate() {
+            let y = if i == 0 { field.one() } else { field.zero() };
+            points.push(Point { x: *root, y });
         }
+
+        Polynomial::create_poly_from_points(points, field)
     }
+}
 
-    pub fn create_poly_from_points(points: Vec<Point>, field: &Field) -> Polynomial {
-        // todo: compute inverse matrix...
-        let mut y_vals = Vec::new();
-        let mut x_vals = Vec::new();
-        for point in points.iter() {
-            x_vals.push(point.x);
-            y_vals.push(point.y);
+// Operators for every mix of owned and borrowed operands, so prover code can write
+// `&a * &b + c * beta` without temporaries
+macro_rules! impl_poly_op {
+    ($trait:ident, $method:ident, |$lhs:ident, $rhs:ident| $body:expr) => {
+        impl $trait<&Polynomial> for &Polynomial {
+            type Output = Polynomial;
+
+            fn $method(self, other: &Polynomial) -> Polynomial {
+                debug_assert_eq!(self.field, other.field, "polynomials over different fields");
+                let $lhs = self;
+                let $rhs = other;
+                $body
+            }
         }
 
-        let interpolation_matrix = Polynomial::get_interpolation_matrix(x_vals, field);
-        let inv_matrix = matrix::get_inverse_matrix_4x4(&interpolation_matrix, field);
-        let coefficients = matrix::matrix_multiply_4x4_1x4(&inv_matrix, &y_vals, field);
+        impl $trait<Polynomial> for &Polynomial {
+            type Output = Polynomial;
 
-        Polynomial {
-            degree: (points.len() - 1) as u32,
-            coefficients,
-            field: field.clone(),
+            fn $method(self, other: Polynomial) -> Polynomial {
+                $trait::$method(self, &other)
+            }
         }
-    }
 
-    fn get_interpolation_matrix(x_vals: Vec<u32>, field: &Field) -> Vec<Vec<u32>> {
-        let mut res = Vec::new();
-        for i in 0..x_vals.len() {
-            res.push(Polynomial::get_interpolation_row(
-                x_vals[i],
-                x_vals.len() as u32,
-                field,
-            ));
+        impl $trait<&Polynomial> for Polynomial {
+            type Output = Polynomial;
+
+            fn $method(self, other: &Polynomial) -> Polynomial {
+                $trait::$method(&self, other)
+            }
         }
 
-        res
-    }
+        impl $trait<Polynomial> for Polynomial {
+            type Output = Polynomial;
 
-    fn get_interpolation_row(x: u32, degree: u32, field: &Field) -> Vec<u32> {
-        let mut res = Vec::new();
-        let mut curr;
-        for i in 0..(degree + 1) {
-            curr = field.exponent(x, i);
-            res.push(curr);
+            fn $method(self, other: Polynomial) -> Polynomial {
+                $trait::$method(&self, &other)
+            }
         }
+    };
+}
 
-        res
+impl_poly_op!(Add, add, |a, b| a.zip_with(b, |x, y| x + y));
+impl_poly_op!(Sub, sub, |a, b| a.zip_with(b, |x, y| x - y));
+impl_poly_op!(Mul, mul, |a, b| a.multiply(b));
+
+// A scalar is the constant polynomial
+impl Add<FieldElement> for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, scalar: FieldElement) -> Polynomial {
+        self + Polynomial::from_scalar(scalar, self.field)
     }
+}
 
-    pub fn get_lagrange_1_poly(field: &Field, degree: u32, roots: &Vec<u32>) -> Polynomial {
-        let mut points = Vec::new();
-        for i in 0..=degree {
-            let y = if i == 0 { 1 } else { 0 };
-            points.push(Point {
-                x: roots[i as usize],
-                y,
-            });
-        }
+impl Add<FieldElement> for Polynomial {
+    type Output = Polynomial;
 
-        Polynomial::create_poly_from_points(points, field)
+    fn add(self, scalar: FieldElement) -> Polynomial {
+        &self + scalar
+    }
+}
+
+impl Sub<FieldElement> for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, scalar: FieldElement) -> Polynomial {
+        self + -scalar
+    }
+}
+
+impl Sub<FieldElement> for Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, scalar: FieldElement) -> Polynomial {
+        &self - scalar
+    }
+}
+
+impl Mul<FieldElement> for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, scalar: FieldElement) -> Polynomial {
+        let coefficients = self.coefficients.iter().map(|c| *c * scalar).collect();
+        Polynomial::new(coefficients, self.field)
+    }
+}
+
+impl Mul<FieldElement> for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, scalar: FieldElement) -> Polynomial {
+        &self * scalar
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        let coefficients = self.coefficients.iter().map(|c| -*c).collect();
+        Polynomial::new(coefficients, self.field)
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        -&self
     }
 }
 
@@ -257,61 +464,193 @@ fn test_create_poly_from_points() {
     let field = Field { order: 17 };
 
     let points = vec![
-        Point { x: 1, y: 3 },
-        Point { x: 4, y: 4 },
-        Point { x: 16, y: 5 },
-        Point { x: 13, y: 9 },
+        Point {
+            x: field.element(1),
+            y: field.element(3),
+        },
+        Point {
+            x: field.element(4),
+            y: field.element(4),
+        },
+        Point {
+            x: field.element(16),
+            y: field.element(5),
+        },
+        Point {
+            x: field.element(13),
+            y: field.element(9),
+        },
     ];
 
     let new_poly = Polynomial::create_poly_from_points(points, &field);
-    let expected_coefficients: Vec<u32> = vec![1, 13, 3, 3];
+    let expected_coefficients: Vec<u64> = vec![1, 13, 3, 3];
 
-    for i in 0..new_poly.coefficients.len() {
-        assert_eq!(new_poly.coefficients[i], expected_coefficients[i]);
-    }
+    assert_eq!(new_poly.coefficients(), expected_coefficients);
 
-    assert_eq!(new_poly.degree, 3);
+    assert_eq!(new_poly.degree(), 3);
 }
 
 #[test]
 fn test_poly_multiply() {
     let field = Field { order: 17 };
-    let poly1 = Polynomial {
-        degree: 2,
-        coefficients: vec![16, 2, 1],
-        field: field.clone(),
-    };
-
-    let poly2 = Polynomial {
-        degree: 2,
-        coefficients: vec![6, 14, 2],
-        field,
-    };
+    let poly1 = Polynomial::new(field.elements(&[16, 2, 1]), field);
+    let poly2 = Polynomial::new(field.elements(&[6, 14, 2]), field);
 
-    let actual_poly = Polynomial::poly_multiply(&poly1, &poly2);
+    let actual_poly = &poly1 * &poly2;
     let expected_coefficients = vec![11, 15, 15, 1, 2];
-    assert_eq!(actual_poly.coefficients, expected_coefficients);
+    assert_eq!(actual_poly.coefficients(), expected_coefficients);
+}
+
+#[test]
+fn test_poly_multiply_ntt_matches_schoolbook() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let poly1 = Polynomial::new((0..70).map(|i| field.element(3 * i + 1)).collect(), field);
+    let poly2 = Polynomial::new((0..40).map(|i| field.element(i * i + 5)).collect(), field);
+
+    let actual_poly = &poly1 * &poly2;
+    assert_eq!(actual_poly.degree(), 108);
+    assert_eq!(
+        actual_poly.coefficients(),
+        Polynomial::schoolbook_multiply(&poly1, &poly2)
+    );
 }
 
 #[test]
 fn test_poly_divide() {
     let field = Field { order: 17 };
 
-    let poly1 = Polynomial {
-        degree: 2,
-        coefficients: vec![2, 1, 2],
-        field: field.clone(),
-    };
+    let poly1 = Polynomial::new(field.elements(&[2, 1, 2]), field);
+    let poly2 = Polynomial::new(field.elements(&[3, 1]), field);
 
-    let poly2 = Polynomial {
-        degree: 1,
-        coefficients: vec![3, 1],
-        field,
-    };
-
-    let (quotient, remainder) = Polynomial::poly_divide(&poly1, &poly2);
+    let (quotient, remainder) = poly1.divide(&poly2);
     let expected_quotient = vec![12, 2];
     let expected_remainder = vec![0];
-    assert_eq!(quotient.coefficients, expected_quotient);
-    assert_eq!(remainder.coefficients, expected_remainder);
+    assert_eq!(quotient.coefficients(), expected_quotient);
+    assert_eq!(remainder.coefficients(), expected_remainder);
+
+    // -3 is a root, so synthetic division agrees and leaves no remainder
+    let (quotient, remainder) = poly1.divide_by_linear(-field.element(3));
+    assert_eq!(quotient.coefficients(), expected_quotient);
+    assert_eq!(remainder, 0);
+
+    let (quotient, remainder) = poly1.divide_by_linear(field.element(2));
+    assert_eq!(remainder, poly1.eval(field.element(2)));
+    assert_eq!(
+        quotient * Polynomial::linear(field.element(2), field) + remainder,
+        poly1
+    );
+}
+
+#[test]
+fn test_divide_by_vanishing() {
+    let field = Field { order: 17 };
+    let domain = EvaluationDomain::new(4, field).unwrap();
+
+    let quotient = Polynomial::new(field.elements(&[5, 0, 3, 9, 1, 16]), field);
+    let remainder = Polynomial::new(field.elements(&[2, 7, 0, 4]), field);
+    let poly = &quotient * domain.vanishing_polynomial() + &remainder;
+
+    assert_eq!(
+        poly.divide_by_vanishing(4),
+        (quotient.clone(), remainder.clone())
+    );
+    assert_eq!(
+        poly.divide(&domain.vanishing_polynomial()),
+        (quotient, remainder)
+    );
+
+    let small = Polynomial::new(field.elements(&[1, 2]), field);
+    assert_eq!(
+        small.divide_by_vanishing(4),
+        (Polynomial::zero(field), small.clone())
+    );
+}
+
+#[test]
+fn test_canonical_form_and_operators() {
+    let field = Field { order: 17 };
+
+    let poly = Polynomial::new(field.elements(&[16, 0, 0, 0]), field);
+    assert_eq!(poly.coefficients(), vec![16]);
+    assert_eq!(poly.degree(), 0);
+    assert!(Polynomial::new(Vec::new(), field).is_zero());
+
+    let a = Polynomial::new(field.elements(&[1, 2, 3]), field);
+    let b = Polynomial::new(field.elements(&[4, 5, 14]), field);
+    // the X^2 terms cancel
+    assert_eq!((&a + &b).coefficients(), vec![5, 7]);
+    assert_eq!((&a - &a).coefficients(), vec![0]);
+    assert_eq!((-&a).coefficients(), vec![16, 15, 14]);
+    assert_eq!((&a * field.element(2)).coefficients(), vec![2, 4, 6]);
+    assert_eq!((&a * field.zero()).coefficients(), vec![0]);
+    assert_eq!((&a + field.element(16)).coefficients(), vec![0, 2, 3]);
+}
+
+#[test]
+fn test_derivative_compose_shift() {
+    let field = Field { order: 17 };
+    let poly = Polynomial::new(field.elements(&[1, 13, 3, 3]), field);
+
+    assert_eq!(poly.derivative().coefficients(), vec![13, 6, 9]);
+    assert!(Polynomial::from_scalar(field.element(5), field)
+        .derivative()
+        .is_zero());
+
+    // p(X + 1)
+    let composed = poly.compose(&Polynomial::new(field.elements(&[1, 1]), field));
+    for x in 0..17 {
+        let x = field.element(x);
+        assert_eq!(composed.eval(x), poly.eval(x + field.one()));
+    }
+
+    assert_eq!(poly.shift(2).coefficients(), vec![0, 0, 1, 13, 3, 3]);
+    assert!(Polynomial::zero(field).shift(3).is_zero());
+
+    let omega = field.element(4);
+    let scaled = poly.scale_input(omega);
+    for x in 0..17 {
+        let x = field.element(x);
+        assert_eq!(scaled.eval(x), poly.eval(omega * x));
+    }
+}
+
+#[test]
+fn test_create_poly_from_arbitrary_points() {
+    let field = Field { order: 17 };
+
+    // the article's left wire values, but at x = 1, 2, 3, 5, 6
+    let x_vals = field.elements(&[1, 2, 3, 5, 6]);
+    let y_vals = field.elements(&[3, 4, 5, 9, 0]);
+    let points = x_vals
+        .iter()
+        .zip(y_vals.iter())
+        .map(|(x, y)| Point { x: *x, y: *y })
+        .collect();
+
+    let new_poly = Polynomial::create_poly_from_points(points, &field);
+    assert_eq!(new_poly.degree(), 4);
+    for (x, y) in x_vals.iter().zip(y_vals.iter()) {
+        assert_eq!(new_poly.eval(*x), *y);
+    }
+
+    let single_point = vec![Point {
+        x: field.element(7),
+        y: field.element(11),
+    }];
+    let constant = Polynomial::create_poly_from_points(single_point, &field);
+    assert_eq!(constant.coefficients(), vec![11]);
+
+    // two domain points {1, 16} take the inverse NTT path
+    let points = vec![
+        Point {
+            x: field.element(1),
+            y: field.element(5),
+        },
+        Point {
+            x: field.element(16),
+            y: field.element(1),
+        },
+    ];
+    let line = Polynomial::create_poly_from_points(points, &field);
+    assert_eq!(line.coefficients(), vec![3, 2]);
 }
@@ -1,37 +1,373 @@
-use crate::math::field::Field;
-pub fn get_roots_of_unity(n: u32, field: &Field) -> Vec<u32> {
-    let mut res = Vec::new();
-    for i in 0..field.order {
-        if field.exponent(i, n) == 1 {
-            res.push(i);
+use crate::math::field::{Field, FieldElement};
+use crate::math::polynomial::Polynomial;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DomainError {
+    SizeNotPowerOfTwo(usize),
+    // the multiplicative group has no subgroup of this size
+    SizeDoesNotDivideGroupOrder(usize),
+    // fewer than three cosets of the subgroup, so k1 and k2 cannot be picked
+    NotEnoughCosets(usize),
+    // the order is not prime, so roots of unity need not exist even if n divides order - 1
+    FieldNotPrime(u64),
+}
+
+// The multiplicative subgroup {ω^0, ..., ω^(n-1)} of a prime field, for n a power of two
+#[derive(Clone, Debug)]
+pub struct EvaluationDomain {
+    pub size: usize,
+    pub field: Field,
+    pub generator: FieldElement,
+    pub generator_inv: FieldElement,
+    pub size_inv: FieldElement,
+    pub elements: Vec<FieldElement>,
+}
+
+impl EvaluationDomain {
+    pub fn new(size: usize, field: Field) -> Result<EvaluationDomain, DomainError> {
+        if !field.is_prime_order() {
+            return Err(DomainError::FieldNotPrime(field.order));
+        }
+        if !size.is_power_of_two() {
+            return Err(DomainError::SizeNotPowerOfTwo(size));
         }
+        if !(field.order - 1).is_multiple_of(size as u64) {
+            return Err(DomainError::SizeDoesNotDivideGroupOrder(size));
+        }
+
+        let generator = EvaluationDomain::find_generator(size, &field);
+        let mut elements = Vec::with_capacity(size);
+        let mut curr = field.one();
+        for _ in 0..size {
+            elements.push(curr);
+            curr *= generator;
+        }
+
+        Ok(EvaluationDomain {
+            size,
+            field,
+            generator,
+            generator_inv: generator.inverse().unwrap(),
+            size_inv: field.element(size as u64).inverse().unwrap(),
+            elements,
+        })
     }
 
-    // Manually switch last two to be in line with plonk_by_hand article
-    res.swap(3, 2);
+    // Picks the numerically smallest primitive n-th root of unity, so the domain is
+    // canonical no matter which element we start from. For n = 4 over F_17 that is
+    // ω = 4, the ordering used by the plonk_by_hand article.
+    fn find_generator(size: usize, field: &Field) -> FieldElement {
+        if size == 1 {
+            return field.one();
+        }
 
-    res
-}
+        let cofactor = (field.order - 1) / size as u64;
+        let mut candidate = field.element(2);
+        let root = loop {
+            // candidate^cofactor has order dividing n, it is primitive iff its
+            // n/2-th power is not 1
+            let root = candidate.pow(cofactor);
+            if root.pow(size as u64 / 2) != field.one() {
+                break root;
+            }
+            candidate += field.one();
+        };
+
+        // for n a power of two, the primitive roots are exactly the odd powers
+        let root_squared = root * root;
+        let mut curr = root;
+        let mut smallest = root;
+        for _ in 0..size / 2 {
+            if curr.value < smallest.value {
+                smallest = curr;
+            }
+            curr *= root_squared;
+        }
+
+        smallest
+    }
+
+    // Picks the smallest k1 and then k2 with H, k1 * H and k2 * H pairwise disjoint.
+    // k * H == H iff k^n == 1, and k1 * H == k2 * H iff k1^n == k2^n.
+    pub fn coset_shifts(&self) -> Result<(FieldElement, FieldElement), DomainError> {
+        if (self.field.order - 1) / (self.size as u64) < 3 {
+            return Err(DomainError::NotEnoughCosets(self.size));
+        }
+
+        let one = self.field.one();
+        let n = self.size as u64;
+
+        let mut k1 = self.field.element(2);
+        while k1.pow(n) == one {
+            k1 += one;
+        }
+
+        let mut k2 = k1 + one;
+        while k2.pow(n) == one || k2.pow(n) == k1.pow(n) {
+            k2 += one;
+        }
+
+        Ok((k1, k2))
+    }
+
+    // Whether k * H is a coset other than H, for shifts not picked here
+    pub fn is_coset_shift(&self, k: FieldElement) -> bool {
+        let k_n = k.pow(self.size as u64);
+        k_n != self.field.zero() && k_n != self.field.one()
+    }
+
+    // Whether H, k1 * H and k2 * H are pairwise disjoint
+    pub fn are_coset_shifts(&self, k1: FieldElement, k2: FieldElement) -> bool {
+        let n = self.size as u64;
+        self.is_coset_shift(k1) && self.is_coset_shift(k2) && k1.pow(n) != k2.pow(n)
+    }
+
+    pub fn coset(&self, k: FieldElement) -> Vec<FieldElement> {
+        self.elements.iter().map(|root| *root * k).collect()
+    }
+
+    // Z_H(X) = X^n - 1
+    pub fn vanishing_polynomial(&self) -> Polynomial {
+        let mut coefficients = vec![self.field.zero(); self.size + 1];
+        coefficients[0] = -self.field.one();
+        coefficients[self.size] = self.field.one();
+        Polynomial::new(coefficients, self.field)
+    }
+
+    pub fn evaluate_vanishing_polynomial(&self, x: FieldElement) -> FieldElement {
+        x.pow(self.size as u64) - self.field.one()
+    }
+
+    // Evaluates the polynomial at ω^0, ..., ω^(n-1). Missing coefficients are zero.
+    // Panics if there are more coefficients than points.
+    pub fn ntt(&self, coefficients: &[FieldElement]) -> Vec<FieldElement> {
+        assert!(
+            coefficients.len() <= self.size,
+            "polynomial has more coefficients than the domain has points"
+        );
+
+        let mut values = coefficients.to_vec();
+        values.resize(self.size, self.field.zero());
+        self.radix_2_transform(&mut values, false);
+        values
+    }
+
+    // Interpolates the polynomial taking the given values at ω^0, ..., ω^(n-1).
+    // Panics unless there is exactly one value per point.
+    pub fn inverse_ntt(&self, evaluations: &[FieldElement]) -> Vec<FieldElement> {
+        assert_eq!(
+            evaluations.len(),
+            self.size,
+            "need one value per domain point"
+        );
+
+        let mut values = evaluations.to_vec();
+        self.radix_2_transform(&mut values, true);
+        for value in values.iter_mut() {
+            *value *= self.size_inv;
+        }
+        values
+    }
+
+    // Evaluates the polynomial at shift * ω^0, ..., shift * ω^(n-1). Panics like ntt.
+    pub fn coset_ntt(
+        &self,
+        coefficients: &[FieldElement],
+        shift: FieldElement,
+    ) -> Vec<FieldElement> {
+        let mut scaled = coefficients.to_vec();
+        let mut shift_power = self.field.one();
+        for coefficient in scaled.iter_mut() {
+            *coefficient *= shift_power;
+            shift_power *= shift;
+        }
 
-pub fn get_coset(k: u32, roots: &Vec<u32>, field: &Field) -> Vec<u32> {
-    roots.iter().map(|root| field.multiply(*root, k)).collect()
+        self.ntt(&scaled)
+    }
+
+    // Interpolates from values at shift * ω^0, ..., shift * ω^(n-1). Panics like
+    // inverse_ntt, or if the shift is zero.
+    pub fn coset_inverse_ntt(
+        &self,
+        evaluations: &[FieldElement],
+        shift: FieldElement,
+    ) -> Vec<FieldElement> {
+        let mut coefficients = self.inverse_ntt(evaluations);
+        let shift_inv = shift.inverse().expect("coset shift must be non-zero");
+        let mut shift_power = self.field.one();
+        for coefficient in coefficients.iter_mut() {
+            *coefficient *= shift_power;
+            shift_power *= shift_inv;
+        }
+
+        coefficients
+    }
+
+    // Iterative Cooley-Tukey, in place. The inverse transform uses ω^-1 and leaves
+    // the division by n to the caller.
+    fn radix_2_transform(&self, values: &mut [FieldElement], inverse: bool) {
+        let n = self.size;
+        let log_n = n.trailing_zeros();
+
+        for i in 0..n {
+            let j = if log_n == 0 {
+                0
+            } else {
+                i.reverse_bits() >> (usize::BITS - log_n)
+            };
+            if i < j {
+                values.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            // ω_len = ω^(n / len), read from the precomputed powers of ω
+            let stride = n / len;
+            for start in (0..n).step_by(len) {
+                for k in 0..len / 2 {
+                    let index = if inverse {
+                        (n - stride * k) % n
+                    } else {
+                        stride * k
+                    };
+                    let twiddle = self.elements[index];
+
+                    let even = values[start + k];
+                    let odd = values[start + k + len / 2] * twiddle;
+                    values[start + k] = even + odd;
+                    values[start + k + len / 2] = even - odd;
+                }
+            }
+            len *= 2;
+        }
+    }
 }
 
 #[test]
 fn test_roots() {
+    let domain = EvaluationDomain::new(4, Field { order: 17 }).unwrap();
     let expected_roots = vec![1, 4, 16, 13];
-    let actual_roots = get_roots_of_unity(4, &Field { order: 17 });
-    assert_eq!(expected_roots, actual_roots);
+    assert_eq!(expected_roots, domain.elements);
+    assert_eq!(domain.generator, 4);
+    assert_eq!(domain.generator_inv, 13);
+    assert_eq!(domain.size_inv * domain.field.element(4), 1);
 }
 
 #[test]
 fn test_cosets() {
-    let field = Field { order: 17 };
+    let domain = EvaluationDomain::new(4, Field { order: 17 }).unwrap();
+    let (k1, k2) = domain.coset_shifts().unwrap();
+    assert_eq!(k1, 2);
+    assert_eq!(k2, 3);
+    assert!(domain.are_coset_shifts(k1, k2));
+
+    // 4 is in H, 8 = 2 * 4 is in k1 * H, and 0 has no coset at all
+    let field = domain.field;
+    assert!(!domain.are_coset_shifts(field.element(4), k2));
+    assert!(!domain.are_coset_shifts(k1, field.element(8)));
+    assert!(!domain.are_coset_shifts(field.zero(), k2));
+
     let expected_coset_1 = vec![2, 8, 15, 9];
-    let actual_coset_1 = get_coset(2, &vec![1, 4, 16, 13], &field);
+    let actual_coset_1 = domain.coset(k1);
     assert_eq!(expected_coset_1, actual_coset_1);
 
     let expected_coset_2 = vec![3, 12, 14, 5];
-    let actual_coset_2 = get_coset(3, &vec![1, 4, 16, 13], &field);
+    let actual_coset_2 = domain.coset(k2);
     assert_eq!(expected_coset_2, actual_coset_2);
 }
+
+#[test]
+fn test_larger_domains() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+    for size in [1, 2, 8, 64, 1024] {
+        let domain = EvaluationDomain::new(size, field).unwrap();
+        assert_eq!(domain.generator.pow(size as u64), 1);
+        if size > 1 {
+            assert_ne!(domain.generator.pow(size as u64 / 2), 1);
+        }
+        assert_eq!(domain.generator * domain.generator_inv, 1);
+
+        let (k1, k2) = domain.coset_shifts().unwrap();
+        let mut all = domain.elements.clone();
+        all.extend(domain.coset(k1));
+        all.extend(domain.coset(k2));
+        all.sort_by_key(|element| element.value);
+        all.dedup();
+        assert_eq!(all.len(), 3 * size);
+    }
+
+    let vanishing = EvaluationDomain::new(8, field)
+        .unwrap()
+        .vanishing_polynomial();
+    assert_eq!(vanishing.coefficients()[0], field.order - 1);
+    assert_eq!(vanishing.coefficients()[8], 1);
+}
+
+#[test]
+fn test_invalid_domains() {
+    let field = Field::new(17);
+    assert_eq!(
+        EvaluationDomain::new(6, field).unwrap_err(),
+        DomainError::SizeNotPowerOfTwo(6)
+    );
+    assert_eq!(
+        EvaluationDomain::new(32, field).unwrap_err(),
+        DomainError::SizeDoesNotDivideGroupOrder(32)
+    );
+    assert_eq!(
+        EvaluationDomain::new(8, field)
+            .unwrap()
+            .coset_shifts()
+            .unwrap_err(),
+        DomainError::NotEnoughCosets(8)
+    );
+
+    // 8 divides 25 - 1, but F_25 is not Z/25, so there is no root to find
+    for order in [0, 1, 25] {
+        assert_eq!(
+            EvaluationDomain::new(8, Field::new(order)).unwrap_err(),
+            DomainError::FieldNotPrime(order)
+        );
+    }
+}
+
+#[test]
+fn test_ntt_round_trip() {
+    let field = Field::new(17);
+    let domain = EvaluationDomain::new(4, field).unwrap();
+
+    // the left wire polynomial from the plonk_by_hand article
+    let coefficients = field.elements(&[1, 13, 3, 3]);
+    let evaluations = domain.ntt(&coefficients);
+    assert_eq!(evaluations, vec![3, 4, 5, 9]);
+    assert_eq!(domain.inverse_ntt(&evaluations), coefficients);
+
+    let shift = field.element(3);
+    let coset_evaluations = domain.coset_ntt(&coefficients, shift);
+    let poly = Polynomial::new(coefficients.clone(), field);
+    for (x, y) in domain.coset(shift).iter().zip(coset_evaluations.iter()) {
+        assert_eq!(poly.eval(*x), *y);
+    }
+    assert_eq!(
+        domain.coset_inverse_ntt(&coset_evaluations, shift),
+        coefficients
+    );
+}
+
+#[test]
+fn test_ntt_matches_naive_evaluation() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let domain = EvaluationDomain::new(64, field).unwrap();
+    let coefficients: Vec<FieldElement> = (0..50).map(|i| field.element(i * i + 7)).collect();
+    let poly = Polynomial::new(coefficients.clone(), field);
+
+    let evaluations = domain.ntt(&coefficients);
+    for (x, y) in domain.elements.iter().zip(evaluations.iter()) {
+        assert_eq!(poly.eval(*x), *y);
+    }
+
+    let mut padded = coefficients;
+    padded.resize(64, field.zero());
+    assert_eq!(domain.inverse_ntt(&evaluations), padded);
+}
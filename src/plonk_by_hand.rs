@@ -1,8 +1,16 @@
+pub mod blinding;
 pub mod circuit;
+pub mod circuit_builder;
+pub mod circuit_definition;
 pub mod constants;
+pub mod json;
 pub mod proof;
 pub mod prover;
 pub mod public_coin;
 pub mod pythagorean_circuit_builder;
+pub mod setup;
 pub mod structured_reference_string;
+#[cfg(test)]
+mod test_utils;
+pub mod transcript;
 pub mod verifier;
@@ -0,0 +1,144 @@
+use crate::math::field::{Field, FieldElement};
+use crate::math::sha256::sha256;
+use std::fs::File;
+use std::io::Read;
+
+// Source of the prover's private blinding scalars. These must never be derived from
+// anything the verifier sees, or the blinded polynomials leak the witness.
+pub trait BlindingRng {
+    // None if the source has run dry, e.g. the OS has no entropy to give
+    fn next_u64(&mut self) -> Option<u64>;
+
+    // Uniform in [0, p) by rejection sampling
+    fn next_scalar(&mut self, field: Field) -> Option<FieldElement> {
+        // the largest multiple of p that fits in a u64, so every residue is equally likely
+        let limit = u64::MAX - (u64::MAX % field.order);
+        loop {
+            let value = self.next_u64()?;
+            if value < limit {
+                return Some(field.element(value));
+            }
+        }
+    }
+}
+
+// Reads /dev/urandom, the default for real proofs. The file is opened on the first
+// draw, so a prover that installs another rng never touches it.
+pub struct OsRng {
+    source: Option<File>,
+}
+
+impl OsRng {
+    pub fn new() -> OsRng {
+        OsRng { source: None }
+    }
+}
+
+impl Default for OsRng {
+    fn default() -> OsRng {
+        OsRng::new()
+    }
+}
+
+impl BlindingRng for OsRng {
+    fn next_u64(&mut self) -> Option<u64> {
+        if self.source.is_none() {
+            self.source = Some(File::open("/dev/urandom").ok()?);
+        }
+        let mut bytes = [0u8; 8];
+        self.source.as_mut()?.read_exact(&mut bytes).ok()?;
+        Some(u64::from_be_bytes(bytes))
+    }
+}
+
+// SHA-256 in counter mode over a seed, for reproducible tests
+pub struct SeededRng {
+    seed: u64,
+    counter: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng { seed, counter: 0 }
+    }
+}
+
+impl BlindingRng for SeededRng {
+    fn next_u64(&mut self) -> Option<u64> {
+        let mut input = self.seed.to_be_bytes().to_vec();
+        input.extend_from_slice(&self.counter.to_be_bytes());
+        self.counter += 1;
+
+        let digest = sha256(&input);
+        Some(u64::from_be_bytes(digest[..8].try_into().unwrap()))
+    }
+}
+
+// Replays the given values in order, cycling when they run out. Used to reproduce the
+// blinding factors from the plonk_by_hand article.
+pub struct FixedBlinding {
+    values: Vec<u64>,
+    next: usize,
+}
+
+impl FixedBlinding {
+    pub fn new(values: &[FieldElement]) -> FixedBlinding {
+        assert!(!values.is_empty(), "FixedBlinding needs at least one value");
+        FixedBlinding {
+            values: values.iter().map(|value| value.value).collect(),
+            next: 0,
+        }
+    }
+}
+
+impl BlindingRng for FixedBlinding {
+    fn next_u64(&mut self) -> Option<u64> {
+        let value = self.values[self.next % self.values.len()];
+        self.next += 1;
+        Some(value)
+    }
+}
+
+#[test]
+fn test_seeded_rng_is_reproducible() {
+    let field = Field::new(17);
+
+    let first: Vec<FieldElement> = {
+        let mut rng = SeededRng::new(42);
+        (0..20).map(|_| rng.next_scalar(field).unwrap()).collect()
+    };
+    let mut rng = SeededRng::new(42);
+    let second: Vec<FieldElement> = (0..20).map(|_| rng.next_scalar(field).unwrap()).collect();
+    assert_eq!(first, second);
+    assert!(first.iter().all(|scalar| scalar.value < 17));
+
+    let mut other = SeededRng::new(43);
+    let third: Vec<FieldElement> = (0..20).map(|_| other.next_scalar(field).unwrap()).collect();
+    assert_ne!(first, third);
+}
+
+#[test]
+fn test_fixed_blinding_replays_values() {
+    let field = Field::new(17);
+    let mut rng = FixedBlinding::new(&field.elements(&[7, 4, 11]));
+
+    let drawn: Vec<FieldElement> = (0..4).map(|_| rng.next_scalar(field).unwrap()).collect();
+    assert_eq!(drawn, vec![7, 4, 11, 7]);
+}
+
+#[test]
+#[should_panic(expected = "FixedBlinding needs at least one value")]
+fn test_fixed_blinding_rejects_empty_values() {
+    FixedBlinding::new(&[]);
+}
+
+#[test]
+fn test_os_rng() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let mut rng = OsRng::new();
+
+    let a = rng.next_scalar(field).unwrap();
+    let b = rng.next_scalar(field).unwrap();
+    assert!(a.value < field.order);
+    assert_ne!(a, b);
+}
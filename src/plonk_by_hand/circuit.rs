@@ -1,24 +1,181 @@
-use crate::math::field::Field;
-use crate::math::polynomial::{Point, Polynomial};
+use crate::math::field::{Field, FieldElement};
+use crate::math::polynomial::Polynomial;
+use crate::math::roots_of_unity::{DomainError, EvaluationDomain};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConstraintError {
+    // computing the witness wrote over this input, as when an input is asserted equal
+    // to a gate output that the other inputs determine
+    Input {
+        wire: usize,
+        expected: FieldElement,
+        actual: FieldElement,
+    },
+    // q_L * a + q_R * b + q_O * c + q_M * a * b + q_C + PI fails at this gate. expected
+    // is the output value the inputs imply, or 0 for the whole sum when q_O = 0.
+    Gate {
+        gate: usize,
+        wire: usize,
+        expected: FieldElement,
+        actual: FieldElement,
+    },
+    // the value at this gate's position differs from the one at the position σ
+    // sends it to
+    Copy {
+        gate: usize,
+        wire: usize,
+        expected: FieldElement,
+        actual: FieldElement,
+    },
+    // σ sends this gate's position to a label that names no position in the domain
+    UnknownCopyLabel {
+        gate: usize,
+        wire: usize,
+        label: FieldElement,
+    },
+    // the gates are not padded to the domain yet, see build_polynomials
+    NotBuilt,
+}
 
-// user must: set_inputs, insert_gate (s), compute_witness, then build_polynomials
+// user must: insert_gate (s) and insert_input (s), build_polynomials, then assign_witness
+#[derive(Clone)]
 pub struct Circuit {
     pub gates: Vec<Gate>,
-    pub inputs: Vec<(u32, usize)>, // (input val, index in witness)
-    pub witness: Vec<u32>,
+    pub real_gate_count: usize, // gates before padding to the domain size
+    pub inputs: Vec<(FieldElement, usize)>, // (input val, index in witness)
+    pub input_wires: Vec<usize>, // wires the prover's inputs go to, in order
+    pub public_input_gates: Vec<usize>, // gate rows whose left wire is public
+    pub witness: Vec<FieldElement>,
     pub field: Field,
-    pub coset1: Vec<u32>,
-    pub coset2: Vec<u32>,
-    pub roots: Vec<u32>,
+    pub domain: EvaluationDomain,
+    pub k1: FieldElement,
+    pub k2: FieldElement,
+    pub coset1: Vec<FieldElement>,
+    pub coset2: Vec<FieldElement>,
     pub circuit_polys: CircuitPolynomials,
 }
 
 impl Circuit {
+    // The domain grows with the gates, see pad_to_domain. k1, k2 and the cosets are
+    // only picked once the gates are padded.
+    pub fn new(field: Field) -> Circuit {
+        Circuit {
+            gates: Vec::new(),
+            real_gate_count: 0,
+            inputs: Vec::new(),
+            input_wires: Vec::new(),
+            public_input_gates: Vec::new(),
+            witness: Vec::new(),
+            field,
+            domain: EvaluationDomain::new(1, field).unwrap(),
+            k1: field.zero(),
+            k2: field.zero(),
+            coset1: Vec::new(),
+            coset2: Vec::new(),
+            circuit_polys: Default::default(),
+        }
+    }
+
+    // Witness slots are allocated as gates reference them
     pub fn insert_gate(&mut self, gate: Gate) {
+        let wires = gate.left_index.max(gate.right_index).max(gate.output_index) + 1;
+        if self.witness.len() < wires {
+            self.witness.resize(wires, self.field.zero());
+        }
+
         self.gates.push(gate);
+        self.real_gate_count += 1;
+    }
+
+    // Fills the gate list up to the next power of two with no-op gates, each on three
+    // fresh zero wires so σ maps its positions to themselves, and sizes the domain to
+    // match. Safe to call again. Fails, before any padding, when the field has no domain
+    // of that size with three cosets.
+    pub fn pad_to_domain(&mut self) -> Result<(), DomainError> {
+        let size = self.gates.len().next_power_of_two();
+        if self.domain.size != size || self.coset1.len() != size {
+            self.set_domain(size)?;
+        }
+
+        while self.gates.len() < size {
+            let wire = self.witness.len();
+            self.witness
+                .extend([self.field.zero(), self.field.zero(), self.field.zero()]);
+            self.gates.push(Gate {
+                left_index: wire,
+                right_index: wire + 1,
+                output_index: wire + 2,
+                gate_type: GateType::NoOp,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn set_domain(&mut self, size: usize) -> Result<(), DomainError> {
+        let domain = EvaluationDomain::new(size, self.field)?;
+        let (k1, k2) = domain.coset_shifts()?;
+
+        self.coset1 = domain.coset(k1);
+        self.coset2 = domain.coset(k2);
+        self.domain = domain;
+        self.k1 = k1;
+        self.k2 = k2;
+        Ok(())
     }
 
-    pub fn set_inputs(&mut self, inputs: Vec<(u32, usize)>) {
+    // Makes a wire public with the gate a + PI(ω^i) = 0, where PI(ω^i) = -x_i. The
+    // unused right and output slots share a fresh zero wire.
+    pub fn insert_public_input(&mut self, wire: usize) {
+        let spare = self.witness.len();
+        self.witness.push(self.field.zero());
+
+        self.public_input_gates.push(self.gates.len());
+        self.insert_gate(Gate {
+            left_index: wire,
+            right_index: spare,
+            output_index: spare,
+            gate_type: GateType::Custom(GateSelectors {
+                left: self.field.one(),
+                right: self.field.zero(),
+                output: self.field.zero(),
+                multiply: self.field.zero(),
+                constant: self.field.zero(),
+            }),
+        });
+    }
+
+    // Marks a wire as the next of the prover's inputs, see assign_witness
+    pub fn insert_input(&mut self, wire: usize) {
+        if self.witness.len() <= wire {
+            self.witness.resize(wire + 1, self.field.zero());
+        }
+
+        self.input_wires.push(wire);
+    }
+
+    // Sets the input wires from the prover's inputs and computes the rest of the witness.
+    // None when there is not exactly one value per input wire.
+    pub fn assign_witness(&mut self, inputs: &[u64]) -> Option<()> {
+        if inputs.len() != self.input_wires.len() {
+            return None;
+        }
+
+        let field = self.field;
+        self.set_inputs(
+            self.input_wires
+                .iter()
+                .zip(inputs.iter())
+                .map(|(wire, input)| (field.element(*input), *wire))
+                .collect(),
+        );
+
+        self.compute_witness();
+        Some(())
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<(FieldElement, usize)>) {
         self.inputs = inputs;
         for (val, index) in self.inputs.iter() {
             self.witness[*index] = *val;
@@ -35,13 +192,30 @@ impl Circuit {
                     gate_type: GateType::Addition,
                     ..
                 } => {
-                    self.witness[gate.output_index] = self.field.add(left_val, right_val);
+                    self.witness[gate.output_index] = left_val + right_val;
                 }
                 Gate {
                     gate_type: GateType::Multiplication,
                     ..
                 } => {
-                    self.witness[gate.output_index] = self.field.multiply(left_val, right_val);
+                    self.witness[gate.output_index] = left_val * right_val;
+                }
+                Gate {
+                    gate_type: GateType::NoOp,
+                    ..
+                } => {}
+                Gate {
+                    gate_type: GateType::Custom(selectors),
+                    ..
+                } => {
+                    // a gate with q_O = 0 only constrains its inputs
+                    if let Some(output_inv) = selectors.output.inverse() {
+                        let rest = selectors.left * left_val
+                            + selectors.right * right_val
+                            + selectors.multiply * left_val * right_val
+                            + selectors.constant;
+                        self.witness[gate.output_index] = -rest * output_inv;
+                    }
                 }
             }
         }
@@ -51,55 +225,137 @@ impl Circuit {
         self.circuit_polys.left = self.build_poly(self.get_left_inputs());
         self.circuit_polys.right = self.build_poly(self.get_right_inputs());
         self.circuit_polys.output = self.build_poly(self.get_outputs());
+        self.circuit_polys.public_input =
+            self.build_poly(self.get_public_input_values(&self.get_public_inputs()));
     }
 
-    pub fn build_polynomials(&mut self) {
+    pub fn build_polynomials(&mut self) -> Result<(), DomainError> {
+        self.pad_to_domain()?;
+
         self.circuit_polys.left_selector = self.build_poly(self.get_left_selectors());
         self.circuit_polys.right_selector = self.build_poly(self.get_right_selectors());
         self.circuit_polys.output_selector = self.build_poly(self.get_output_selectors());
         self.circuit_polys.multiply_selector = self.build_poly(self.get_multiply_selectors());
+        self.circuit_polys.constant_selector = self.build_poly(self.get_constant_selectors());
         self.circuit_polys.left_copy = self.build_poly(self.get_left_copy_constraints());
         self.circuit_polys.right_copy = self.build_poly(self.get_right_copy_constraints());
         self.circuit_polys.output_copy = self.build_poly(self.get_output_copy_constraints());
         self.circuit_polys.z_h = self.get_z_h();
+        Ok(())
     }
 
-    pub fn build_accumulator(&mut self, beta: u32, gamma: u32) {
-        // left, right, and output copy polys must be initialized first
-        self.circuit_polys.acc = self.build_poly(self.get_accumulator_values(
-            self.circuit_polys.left.degree,
-            beta,
-            gamma,
-        ));
+    // Checks the witness against every gate and against the σ polynomials the proof
+    // will commit to, after build_polynomials and compute_witness
+    pub fn check_satisfied(&self) -> Result<(), ConstraintError> {
+        if self.gates.len() != self.domain.size || self.coset1.len() != self.domain.size {
+            return Err(ConstraintError::NotBuilt);
+        }
+
+        for (value, wire) in self.inputs.iter() {
+            if self.witness[*wire] != *value {
+                return Err(ConstraintError::Input {
+                    wire: *wire,
+                    expected: *value,
+                    actual: self.witness[*wire],
+                });
+            }
+        }
+
+        let columns = [
+            self.get_left_inputs(),
+            self.get_right_inputs(),
+            self.get_outputs(),
+        ];
+        let public_inputs = self.get_public_input_values(&self.get_public_inputs());
+
+        for (i, gate) in self.gates.iter().enumerate() {
+            let (a, b, c) = (columns[0][i], columns[1][i], columns[2][i]);
+            let selectors = gate.gate_type.selectors(self.field);
+            let rest = selectors.left * a
+                + selectors.right * b
+                + selectors.multiply * a * b
+                + selectors.constant
+                + public_inputs[i];
+
+            match selectors.output.inverse() {
+                Some(output_inv) if c != -rest * output_inv => {
+                    return Err(ConstraintError::Gate {
+                        gate: i,
+                        wire: gate.output_index,
+                        expected: -rest * output_inv,
+                        actual: c,
+                    });
+                }
+                None if !rest.is_zero() => {
+                    return Err(ConstraintError::Gate {
+                        gate: i,
+                        wire: gate.left_index,
+                        expected: self.field.zero(),
+                        actual: rest,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        // which (column, gate) each label ω^i, k1 * ω^i, k2 * ω^i names
+        let mut positions = HashMap::new();
+        for (row, root) in self.domain.elements.iter().enumerate() {
+            positions.insert(root.value, (0, row));
+            positions.insert(self.coset1[row].value, (1, row));
+            positions.insert(self.coset2[row].value, (2, row));
+        }
+
+        let polys = &self.circuit_polys;
+        for (column, sigma_poly) in [&polys.left_copy, &polys.right_copy, &polys.output_copy]
+            .into_iter()
+            .enumerate()
+        {
+            let sigma = self.domain.ntt(sigma_poly.coefficients());
+            for (row, label) in sigma.iter().enumerate() {
+                let gate = &self.gates[row];
+                let wire = [gate.left_index, gate.right_index, gate.output_index][column];
+                let Some(&(target_column, target_row)) = positions.get(&label.value) else {
+                    return Err(ConstraintError::UnknownCopyLabel {
+                        gate: row,
+                        wire,
+                        label: *label,
+                    });
+                };
+
+                let expected = columns[target_column][target_row];
+                if columns[column][row] != expected {
+                    return Err(ConstraintError::Copy {
+                        gate: row,
+                        wire,
+                        expected,
+                        actual: columns[column][row],
+                    });
+                }
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn build_poly(&self, y_vals: Vec<u32>) -> Polynomial {
-        let points = y_vals
-            .iter()
-            .zip(self.roots.iter())
-            .map(|(input, root)| Point {
-                x: *root,
-                y: *input,
-            })
-            .collect();
+    // None when beta and gamma make a denominator vanish
+    pub fn build_accumulator(&mut self, beta: FieldElement, gamma: FieldElement) -> Option<()> {
+        // left, right, and output copy polys must be initialized first
+        self.circuit_polys.acc = self.build_poly(self.get_accumulator_values(beta, gamma)?);
+        Some(())
+    }
 
-        Polynomial::create_poly_from_points(points, &self.field)
+    // The domain points are roots of unity, so interpolation is an inverse NTT
+    pub fn build_poly(&self, y_vals: Vec<FieldElement>) -> Polynomial {
+        Polynomial::new(self.domain.inverse_ntt(&y_vals), self.field)
     }
 
     pub fn get_z_h(&self) -> Polynomial {
-        let degree = self.roots.len() as u32;
-        let mut coefficients = vec![0; (degree + 1) as usize];
-        coefficients[0] = self.field.additive_inverse(1);
-        coefficients[degree as usize] = 1;
-        Polynomial {
-            degree,
-            coefficients,
-            field: self.field.clone(),
-        }
+        self.domain.vanishing_polynomial()
     }
 
-    pub fn get_left_inputs(&self) -> Vec<u32> {
-        let mut left_inputs: Vec<u32> = Vec::new();
+    pub fn get_left_inputs(&self) -> Vec<FieldElement> {
+        let mut left_inputs: Vec<FieldElement> = Vec::new();
         for gate in self.gates.iter() {
             left_inputs.push(self.witness[gate.left_index]);
         }
@@ -107,8 +363,8 @@ impl Circuit {
         left_inputs
     }
 
-    pub fn get_right_inputs(&self) -> Vec<u32> {
-        let mut right_inputs: Vec<u32> = Vec::new();
+    pub fn get_right_inputs(&self) -> Vec<FieldElement> {
+        let mut right_inputs: Vec<FieldElement> = Vec::new();
         for gate in self.gates.iter() {
             right_inputs.push(self.witness[gate.right_index]);
         }
@@ -116,8 +372,8 @@ impl Circuit {
         right_inputs
     }
 
-    pub fn get_outputs(&self) -> Vec<u32> {
-        let mut outputs: Vec<u32> = Vec::new();
+    pub fn get_outputs(&self) -> Vec<FieldElement> {
+        let mut outputs: Vec<FieldElement> = Vec::new();
         for gate in self.gates.iter() {
             outputs.push(self.witness[gate.output_index]);
         }
@@ -125,208 +381,194 @@ impl Circuit {
         outputs
     }
 
-    pub fn get_left_selectors(&self) -> Vec<u32> {
-        self.gates
+    pub fn get_public_inputs(&self) -> Vec<FieldElement> {
+        self.public_input_gates
             .iter()
-            .map(|gate| {
-                if let Gate {
-                    gate_type: GateType::Addition,
-                    ..
-                } = gate
-                {
-                    1
-                } else {
-                    0
-                }
-            })
+            .map(|row| self.witness[self.gates[*row].left_index])
             .collect()
     }
 
-    pub fn get_right_selectors(&self) -> Vec<u32> {
-        self.gates
-            .iter()
-            .map(|gate| {
-                if let Gate {
-                    gate_type: GateType::Addition,
-                    ..
-                } = gate
-                {
-                    1
-                } else {
-                    0
-                }
-            })
-            .collect()
+    // PI(ω^i) = -x_i on public input rows and 0 elsewhere
+    pub fn get_public_input_values(&self, public_inputs: &[FieldElement]) -> Vec<FieldElement> {
+        let mut values = vec![self.field.zero(); self.gates.len()];
+        for (row, input) in self.public_input_gates.iter().zip(public_inputs.iter()) {
+            values[*row] = -*input;
+        }
+
+        values
     }
 
-    pub fn get_output_selectors(&self) -> Vec<u32> {
-        self.gates
-            .iter()
-            .map(|_| self.field.additive_inverse(1))
-            .collect()
+    pub fn get_left_selectors(&self) -> Vec<FieldElement> {
+        self.get_selectors(|selectors| selectors.left)
+    }
+
+    pub fn get_right_selectors(&self) -> Vec<FieldElement> {
+        self.get_selectors(|selectors| selectors.right)
+    }
+
+    pub fn get_output_selectors(&self) -> Vec<FieldElement> {
+        self.get_selectors(|selectors| selectors.output)
+    }
+
+    pub fn get_multiply_selectors(&self) -> Vec<FieldElement> {
+        self.get_selectors(|selectors| selectors.multiply)
     }
 
-    pub fn get_multiply_selectors(&self) -> Vec<u32> {
+    pub fn get_constant_selectors(&self) -> Vec<FieldElement> {
+        self.get_selectors(|selectors| selectors.constant)
+    }
+
+    fn get_selectors(&self, pick: impl Fn(&GateSelectors) -> FieldElement) -> Vec<FieldElement> {
         self.gates
             .iter()
-            .map(|gate| {
-                if let Gate {
-                    gate_type: GateType::Multiplication,
-                    ..
-                } = gate
-                {
-                    1
-                } else {
-                    0
-                }
-            })
+            .map(|gate| pick(&gate.gate_type.selectors(self.field)))
             .collect()
     }
 
-    pub fn get_left_copy_constraints(&self) -> Vec<u32> {
-        let mut result = Vec::new();
-        for i in 0..self.gates.len() {
-            let left_index = self.gates[i].left_index;
+    pub fn get_left_copy_constraints(&self) -> Vec<FieldElement> {
+        let [left, _, _] = self.get_copy_permutation();
+        left
+    }
 
-            for k in 0..self.gates.len() {
-                if self.gates[k].right_index == left_index {
-                    result.push(self.coset1[k]);
-                } else if self.gates[k].output_index == left_index {
-                    result.push(self.coset2[k]);
-                } else if self.gates[k].left_index == left_index && k != i {
-                    result.push(self.roots[k]);
-                }
-            }
-        }
-        result
-    }
-
-    pub fn get_right_copy_constraints(&self) -> Vec<u32> {
-        let mut result = Vec::new();
-        for i in 0..self.gates.len() {
-            let right_index = self.gates[i].right_index;
-            for k in 0..self.gates.len() {
-                if self.gates[k].left_index == right_index {
-                    result.push(self.roots[k]);
-                } else if self.gates[k].right_index == right_index {
-                    if k != i {
-                        result.push(self.coset1[k]);
-                    }
-                } else if self.gates[k].output_index == right_index {
-                    result.push(self.coset2[k]);
-                }
+    pub fn get_right_copy_constraints(&self) -> Vec<FieldElement> {
+        let [_, right, _] = self.get_copy_permutation();
+        right
+    }
+
+    pub fn get_output_copy_constraints(&self) -> Vec<FieldElement> {
+        let [_, _, output] = self.get_copy_permutation();
+        output
+    }
+
+    // σ over the left, right and output columns. Position (column j, gate i) is labelled
+    // ω^i, k1 * ω^i or k2 * ω^i. The positions holding one wire form a cycle, each
+    // mapped to the next one's label, so a wire used once maps to itself.
+    pub fn get_copy_permutation(&self) -> [Vec<FieldElement>; 3] {
+        let rows = self.gates.len();
+        let labels = [
+            self.domain.elements[..rows].to_vec(),
+            self.coset1[..rows].to_vec(),
+            self.coset2[..rows].to_vec(),
+        ];
+
+        // every (column, row) holding each wire, in gate order
+        let mut cycles = vec![Vec::new(); self.witness.len()];
+        for (row, gate) in self.gates.iter().enumerate() {
+            for (column, wire) in [gate.left_index, gate.right_index, gate.output_index]
+                .into_iter()
+                .enumerate()
+            {
+                cycles[wire].push((column, row));
             }
         }
-        result
-    }
-
-    pub fn get_output_copy_constraints(&self) -> Vec<u32> {
-        let mut result = Vec::new();
-        for i in 0..self.gates.len() {
-            let output_index = self.gates[i].output_index;
-            for k in 0..self.gates.len() {
-                if self.gates[k].left_index == output_index {
-                    result.push(self.roots[k]);
-                } else if self.gates[k].right_index == output_index {
-                    result.push(self.coset1[k]);
-                } else if self.gates[k].output_index == output_index && k != i {
-                    result.push(self.coset2[k]);
-                }
+
+        let mut sigma = labels.clone();
+        for cycle in cycles.iter() {
+            for (i, (column, row)) in cycle.iter().enumerate() {
+                let (next_column, next_row) = cycle[(i + 1) % cycle.len()];
+                sigma[*column][*row] = labels[next_column][next_row];
             }
         }
-        result
+
+        sigma
     }
 
-    pub fn get_accumulator_values(&self, degree: u32, beta: u32, gamma: u32) -> Vec<u32> {
-        let mut res = Vec::new();
+    // Z(ω^0) = 1, then one running product step per gate except the last
+    pub fn get_accumulator_values(
+        &self,
+        beta: FieldElement,
+        gamma: FieldElement,
+    ) -> Option<Vec<FieldElement>> {
+        let rounds = self.domain.size - 1;
         let a = self.get_left_inputs();
         let b = self.get_right_inputs();
         let c = self.get_outputs();
-
-        res.push(1);
-        for round in 0..degree {
-            let mut new_acc_numerator = self.field.add(
-                a[round as usize],
-                self.field
-                    .add(self.field.multiply(beta, self.roots[round as usize]), gamma),
-            );
-            new_acc_numerator = self.field.multiply(
-                new_acc_numerator,
-                self.field.add(
-                    b[round as usize],
-                    self.field.add(
-                        self.field.multiply(beta, self.coset1[round as usize]),
-                        gamma,
-                    ),
-                ),
-            );
-            new_acc_numerator = self.field.multiply(
-                new_acc_numerator,
-                self.field.add(
-                    c[round as usize],
-                    self.field.add(
-                        self.field.multiply(beta, self.coset2[round as usize]),
-                        gamma,
-                    ),
-                ),
+        // σ on H in one transform each, rather than evaluating every polynomial per row
+        let polys = &self.circuit_polys;
+        let sigma_1 = self.domain.ntt(polys.left_copy.coefficients());
+        let sigma_2 = self.domain.ntt(polys.right_copy.coefficients());
+        let sigma_3 = self.domain.ntt(polys.output_copy.coefficients());
+
+        let mut numerators = Vec::new();
+        let mut denominators = Vec::new();
+        for round in 0..rounds {
+            let root = self.domain.elements[round];
+
+            numerators.push(
+                (a[round] + beta * root + gamma)
+                    * (b[round] + beta * self.coset1[round] + gamma)
+                    * (c[round] + beta * self.coset2[round] + gamma),
             );
 
-            let mut new_acc_denominator = self.field.add(
-                a[round as usize],
-                self.field.add(
-                    self.field.multiply(
-                        beta,
-                        self.circuit_polys
-                            .left_copy
-                            .eval(self.roots[round as usize]),
-                    ),
-                    gamma,
-                ),
-            );
-            new_acc_denominator = self.field.multiply(
-                new_acc_denominator,
-                self.field.add(
-                    b[round as usize],
-                    self.field.add(
-                        self.field.multiply(
-                            beta,
-                            self.circuit_polys
-                                .right_copy
-                                .eval(self.roots[round as usize]),
-                        ),
-                        gamma,
-                    ),
-                ),
-            );
-            new_acc_denominator = self.field.multiply(
-                new_acc_denominator,
-                self.field.add(
-                    c[round as usize],
-                    self.field.add(
-                        self.field.multiply(
-                            beta,
-                            self.circuit_polys
-                                .output_copy
-                                .eval(self.roots[round as usize]),
-                        ),
-                        gamma,
-                    ),
-                ),
+            denominators.push(
+                (a[round] + beta * sigma_1[round] + gamma)
+                    * (b[round] + beta * sigma_2[round] + gamma)
+                    * (c[round] + beta * sigma_3[round] + gamma),
             );
+        }
+
+        let denominator_inverses = self.field.batch_inverse(&denominators)?;
 
-            let acc_i = self.field.divide(new_acc_numerator, new_acc_denominator);
-            res.push(self.field.multiply(res[(round) as usize], acc_i));
+        let mut res = vec![self.field.one()];
+        for round in 0..rounds {
+            res.push(res[round] * numerators[round] * denominator_inverses[round]);
         }
 
-        res
+        Some(res)
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum GateType {
     Addition,
     Multiplication,
+    Custom(GateSelectors),
+    // pads the circuit to the domain size, every selector is zero
+    NoOp,
+}
+
+impl GateType {
+    pub fn selectors(&self, field: Field) -> GateSelectors {
+        match self {
+            // a + b - c = 0
+            GateType::Addition => GateSelectors {
+                left: field.one(),
+                right: field.one(),
+                output: -field.one(),
+                multiply: field.zero(),
+                constant: field.zero(),
+            },
+            // a * b - c = 0
+            GateType::Multiplication => GateSelectors {
+                left: field.zero(),
+                right: field.zero(),
+                output: -field.one(),
+                multiply: field.one(),
+                constant: field.zero(),
+            },
+            GateType::Custom(selectors) => *selectors,
+            GateType::NoOp => GateSelectors {
+                left: field.zero(),
+                right: field.zero(),
+                output: field.zero(),
+                multiply: field.zero(),
+                constant: field.zero(),
+            },
+        }
+    }
+}
+
+// q_L * a + q_R * b + q_O * c + q_M * a * b + q_C = 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GateSelectors {
+    pub left: FieldElement,
+    pub right: FieldElement,
+    pub output: FieldElement,
+    pub multiply: FieldElement,
+    pub constant: FieldElement,
 }
 
+#[derive(Clone)]
 pub struct Gate {
     pub left_index: usize,
     pub right_index: usize,
@@ -334,7 +576,7 @@ pub struct Gate {
     pub gate_type: GateType,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct CircuitPolynomials {
     pub left: Polynomial,
     pub right: Polynomial,
@@ -343,9 +585,218 @@ pub struct CircuitPolynomials {
     pub right_selector: Polynomial,
     pub output_selector: Polynomial,
     pub multiply_selector: Polynomial,
+    pub constant_selector: Polynomial,
+    pub public_input: Polynomial,
     pub left_copy: Polynomial,
     pub right_copy: Polynomial,
     pub output_copy: Polynomial,
     pub z_h: Polynomial,
     pub acc: Polynomial,
 }
+
+#[test]
+fn test_custom_gate_selectors_and_witness() {
+    let field = Field::new(17);
+    let mut circuit = Circuit::new(field);
+
+    // 3a - 2b + ab + 7 = c
+    circuit.insert_gate(Gate {
+        left_index: 0,
+        right_index: 1,
+        output_index: 2,
+        gate_type: GateType::Custom(GateSelectors {
+            left: field.element(3),
+            right: -field.element(2),
+            output: -field.one(),
+            multiply: field.one(),
+            constant: field.element(7),
+        }),
+    });
+    circuit.insert_gate(Gate {
+        left_index: 2,
+        right_index: 1,
+        output_index: 3,
+        gate_type: GateType::Addition,
+    });
+    circuit.set_inputs(vec![(field.element(4), 0), (field.element(6), 1)]);
+    circuit.compute_witness();
+
+    // 12 - 12 + 24 + 7 = 31 = 14
+    assert_eq!(circuit.witness, vec![4, 6, 14, 3]);
+    assert_eq!(circuit.get_left_selectors(), vec![3, 1]);
+    assert_eq!(circuit.get_right_selectors(), vec![15, 1]);
+    assert_eq!(circuit.get_output_selectors(), vec![16, 16]);
+    assert_eq!(circuit.get_multiply_selectors(), vec![1, 0]);
+    assert_eq!(circuit.get_constant_selectors(), vec![7, 0]);
+}
+
+#[test]
+fn test_copy_permutation_fan_out() {
+    let field = Field::new(17);
+    let mut circuit = Circuit::new(field);
+
+    // wire 0 fans out to four positions, wire 4 is used once
+    for (left_index, right_index, output_index) in [(0, 0, 1), (0, 1, 2), (2, 0, 3), (3, 4, 3)] {
+        circuit.insert_gate(Gate {
+            left_index,
+            right_index,
+            output_index,
+            gate_type: GateType::Addition,
+        });
+    }
+    assert_eq!(circuit.pad_to_domain(), Ok(()));
+
+    let [left, right, output] = circuit.get_copy_permutation();
+    assert_eq!(left.len(), 4);
+    assert_eq!(right.len(), 4);
+    assert_eq!(output.len(), 4);
+
+    let label = |column: usize, row: usize| match column {
+        0 => circuit.domain.elements[row],
+        1 => circuit.coset1[row],
+        _ => circuit.coset2[row],
+    };
+    // wire 0: (L, 0) -> (R, 0) -> (L, 1) -> (R, 2) -> (L, 0)
+    assert_eq!(left[0], label(1, 0));
+    assert_eq!(right[0], label(0, 1));
+    assert_eq!(left[1], label(1, 2));
+    assert_eq!(right[2], label(0, 0));
+    // wire 3: (O, 2) -> (L, 3) -> (O, 3) -> (O, 2)
+    assert_eq!(output[2], label(0, 3));
+    assert_eq!(left[3], label(2, 3));
+    assert_eq!(output[3], label(2, 2));
+    // wire 4 is only used once
+    assert_eq!(right[3], label(1, 3));
+
+    // σ permutes the labels
+    let mut permuted: Vec<u64> = [left, right, output]
+        .concat()
+        .iter()
+        .map(|x| x.value)
+        .collect();
+    let mut labels: Vec<u64> = (0..3)
+        .flat_map(|column| (0..4).map(move |row| (column, row)))
+        .map(|(column, row)| label(column, row).value)
+        .collect();
+    permuted.sort();
+    labels.sort();
+    assert_eq!(permuted, labels);
+}
+
+#[test]
+fn test_pad_to_domain() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let mut circuit = Circuit::new(field);
+    for i in 0..5 {
+        circuit.insert_gate(Gate {
+            left_index: i,
+            right_index: i,
+            output_index: i + 1,
+            gate_type: GateType::Multiplication,
+        });
+    }
+    assert_eq!(circuit.build_polynomials(), Ok(()));
+
+    assert_eq!(circuit.real_gate_count, 5);
+    assert_eq!(circuit.gates.len(), 8);
+    assert_eq!(circuit.domain.size, 8);
+    // 6 real wires, then three fresh ones per no-op gate
+    assert_eq!(circuit.witness.len(), 6 + 3 * 3);
+
+    assert_eq!(circuit.get_multiply_selectors()[5..], [0, 0, 0]);
+    assert_eq!(circuit.get_output_selectors()[5..], [0, 0, 0]);
+    let [left, right, output] = circuit.get_copy_permutation();
+    for row in 5..8 {
+        assert_eq!(left[row], circuit.domain.elements[row]);
+        assert_eq!(right[row], circuit.coset1[row]);
+        assert_eq!(output[row], circuit.coset2[row]);
+    }
+
+    // padding again changes nothing
+    assert_eq!(circuit.pad_to_domain(), Ok(()));
+    assert_eq!(circuit.gates.len(), 8);
+    // F_17 has a subgroup of order 8, but only two cosets of it
+    let mut circuit = Circuit::new(Field::new(17));
+    for i in 0..5 {
+        circuit.insert_gate(Gate {
+            left_index: i,
+            right_index: i,
+            output_index: i + 1,
+            gate_type: GateType::Multiplication,
+        });
+    }
+    assert_eq!(
+        circuit.build_polynomials(),
+        Err(DomainError::NotEnoughCosets(8))
+    );
+    assert_eq!(circuit.gates.len(), 5);
+}
+
+#[test]
+fn test_check_satisfied() {
+    use crate::plonk_by_hand::pythagorean_circuit_builder::PythagoreanCircuit;
+
+    let field = Field::new(17);
+    let mut circuit = PythagoreanCircuit::new(field);
+    circuit.build_circuit_with_inputs(vec![3, 4, 5]);
+    assert_eq!(circuit.circuit.check_satisfied(), Ok(()));
+
+    // 6^2 = 2, but the sum gate writes 3^2 + 4^2 = 8 to the shared output wire
+    let mut circuit = PythagoreanCircuit::new(field);
+    circuit.build_circuit_with_inputs(vec![3, 4, 6]);
+    assert_eq!(
+        circuit.circuit.check_satisfied(),
+        Err(ConstraintError::Gate {
+            gate: 2,
+            wire: 5,
+            expected: field.element(2),
+            actual: field.element(8),
+        })
+    );
+
+    // rewiring a gate after σ was built breaks a copy constraint
+    let mut circuit = PythagoreanCircuit::new(field);
+    circuit.build_circuit_with_inputs(vec![3, 4, 5]);
+    circuit.circuit.gates[3].left_index = 0;
+    circuit.circuit.gates[3].gate_type = GateType::Custom(GateSelectors {
+        left: field.zero(),
+        right: field.zero(),
+        output: field.zero(),
+        multiply: field.zero(),
+        constant: field.zero(),
+    });
+    assert_eq!(
+        circuit.circuit.check_satisfied(),
+        Err(ConstraintError::Copy {
+            gate: 3,
+            wire: 0,
+            expected: field.element(9),
+            actual: field.element(3),
+        })
+    );
+
+    // σ may only name positions in the domain
+    let mut circuit = PythagoreanCircuit::new(field);
+    circuit.build_circuit_with_inputs(vec![3, 4, 5]);
+    circuit.circuit.circuit_polys.left_copy = Polynomial::new(vec![field.zero()], field);
+    assert_eq!(
+        circuit.circuit.check_satisfied(),
+        Err(ConstraintError::UnknownCopyLabel {
+            gate: 0,
+            wire: 0,
+            label: field.zero(),
+        })
+    );
+
+    // five gates before padding to eight
+    let mut circuit = Circuit::new(field);
+    for _ in 0..5 {
+        circuit.insert_gate(Gate {
+            left_index: 0,
+            right_index: 1,
+            output_index: 2,
+            gate_type: GateType::Addition,
+        });
+    }
+    assert_eq!(circuit.check_satisfied(), Err(ConstraintError::NotBuilt));
+}
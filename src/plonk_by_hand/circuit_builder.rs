@@ -0,0 +1,348 @@
+use crate::math::field::Field;
+use crate::math::roots_of_unity::DomainError;
+use crate::plonk_by_hand::circuit::{Circuit, Gate, GateSelectors, GateType};
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
+#[cfg(test)]
+use crate::plonk_by_hand::pythagorean_circuit_builder::PythagoreanCircuit;
+
+// Handle to a value in the circuit. Variables asserted equal share one witness slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Variable(usize);
+
+// Builds a circuit from operations on variables instead of raw witness indices:
+//
+//     let a = builder.input();
+//     let b = builder.input();
+//     let c = builder.public_input();
+//     let a_squared = builder.mul(a, a);
+//     let b_squared = builder.mul(b, b);
+//     let sum = builder.add(a_squared, b_squared);
+//     let c_squared = builder.mul(c, c);
+//     builder.assert_equal(sum, c_squared);
+//     let circuit = builder.build();
+pub struct CircuitBuilder {
+    field: Field,
+    gates: Vec<(Variable, Variable, Variable, GateType)>,
+    // union-find over variables, so assert_equal merges witness slots
+    parents: Vec<usize>,
+    inputs: Vec<Variable>,
+    public_inputs: Vec<Variable>,
+}
+
+impl CircuitBuilder {
+    pub fn new(field: Field) -> CircuitBuilder {
+        CircuitBuilder {
+            field,
+            gates: Vec::new(),
+            parents: Vec::new(),
+            inputs: Vec::new(),
+            public_inputs: Vec::new(),
+        }
+    }
+
+    // A private input, assigned by the prover in the order inputs were created
+    pub fn input(&mut self) -> Variable {
+        let variable = self.new_variable();
+        self.inputs.push(variable);
+        variable
+    }
+
+    // An input the verifier also supplies. The prover assigns it like any other input.
+    pub fn public_input(&mut self) -> Variable {
+        let variable = self.input();
+        self.public_inputs.push(variable);
+        variable
+    }
+
+    // k, fixed by the gate -c + k = 0
+    pub fn constant(&mut self, k: u64) -> Variable {
+        let output = self.new_variable();
+        let selectors = GateSelectors {
+            left: self.field.zero(),
+            right: self.field.zero(),
+            output: -self.field.one(),
+            multiply: self.field.zero(),
+            constant: self.field.element(k),
+        };
+        // q_L = q_R = 0, so the unused slots are wired to the output itself
+        self.gates
+            .push((output, output, output, GateType::Custom(selectors)));
+        output
+    }
+
+    pub fn add(&mut self, left: Variable, right: Variable) -> Variable {
+        self.gate(left, right, GateType::Addition)
+    }
+
+    pub fn mul(&mut self, left: Variable, right: Variable) -> Variable {
+        self.gate(left, right, GateType::Multiplication)
+    }
+
+    // A copy constraint, no gate: both variables end up in the same witness slot
+    pub fn assert_equal(&mut self, a: Variable, b: Variable) {
+        let root_a = self.find(a.0);
+        let root_b = self.find(b.0);
+        // the older variable stays the root, so slots follow creation order
+        self.parents[root_a.max(root_b)] = root_a.min(root_b);
+    }
+
+    // Lays out the gates, then one public input gate per public input
+    pub fn build(mut self) -> BuiltCircuit {
+        // number the distinct slots in creation order
+        let mut slots = vec![None; self.parents.len()];
+        let mut wires = Vec::new();
+        let mut num_wires = 0;
+        for variable in 0..self.parents.len() {
+            let root = self.find(variable);
+            if slots[root].is_none() {
+                slots[root] = Some(num_wires);
+                num_wires += 1;
+            }
+            wires.push(slots[root].unwrap());
+        }
+        let wire = |variable: Variable| wires[variable.0];
+
+        let mut circuit = Circuit::new(self.field);
+        // inputs no gate reads still get a slot
+        circuit.witness = vec![self.field.zero(); num_wires];
+        for (left, right, output, gate_type) in self.gates {
+            circuit.insert_gate(Gate {
+                left_index: wire(left),
+                right_index: wire(right),
+                output_index: wire(output),
+                gate_type,
+            });
+        }
+        for input in self.inputs {
+            circuit.insert_input(wire(input));
+        }
+        for input in self.public_inputs {
+            circuit.insert_public_input(wire(input));
+        }
+
+        BuiltCircuit { circuit }
+    }
+
+    fn gate(&mut self, left: Variable, right: Variable, gate_type: GateType) -> Variable {
+        let output = self.new_variable();
+        self.gates.push((left, right, output, gate_type));
+        output
+    }
+
+    fn new_variable(&mut self) -> Variable {
+        self.parents.push(self.parents.len());
+        Variable(self.parents.len() - 1)
+    }
+
+    fn find(&mut self, variable: usize) -> usize {
+        let parent = self.parents[variable];
+        if parent == variable {
+            return variable;
+        }
+
+        let root = self.find(parent);
+        self.parents[variable] = root;
+        root
+    }
+}
+
+// The builder's output, ready for Prover::new and Verifier::new
+#[derive(Clone)]
+pub struct BuiltCircuit {
+    circuit: Circuit,
+}
+
+impl CircuitDefinition for BuiltCircuit {
+    fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    fn circuit_mut(&mut self) -> &mut Circuit {
+        &mut self.circuit
+    }
+
+    // the builder already inserted the gates
+    fn build_gates(&mut self) -> Result<(), DomainError> {
+        self.circuit.build_polynomials()
+    }
+}
+
+#[cfg(test)]
+fn build_pythagorean(field: Field) -> BuiltCircuit {
+    let mut builder = CircuitBuilder::new(field);
+    let a = builder.input();
+    let b = builder.input();
+    let c = builder.input();
+    let a_squared = builder.mul(a, a);
+    let b_squared = builder.mul(b, b);
+    let c_squared = builder.mul(c, c);
+    let sum = builder.add(a_squared, b_squared);
+    builder.assert_equal(sum, c_squared);
+
+    builder.build()
+}
+
+#[test]
+fn test_builder_matches_pythagorean_circuit() {
+    use crate::plonk_by_hand::constants;
+
+    let field = constants::FIELD_17;
+    let mut built = build_pythagorean(field);
+    built.build_gates().unwrap();
+    built.circuit.assign_witness(&[3, 4, 5]);
+    built.circuit.build_polynomials_with_input();
+
+    let mut by_hand = PythagoreanCircuit::new(field);
+    by_hand.build_circuit_with_inputs(vec![3, 4, 5]);
+
+    // the same gates up to witness numbering, so the same polynomials
+    let built_polys = &built.circuit.circuit_polys;
+    let by_hand_polys = &by_hand.circuit.circuit_polys;
+    for (built_poly, by_hand_poly) in [
+        (&built_polys.left, &by_hand_polys.left),
+        (&built_polys.right, &by_hand_polys.right),
+        (&built_polys.output, &by_hand_polys.output),
+        (&built_polys.left_selector, &by_hand_polys.left_selector),
+        (
+            &built_polys.multiply_selector,
+            &by_hand_polys.multiply_selector,
+        ),
+        (&built_polys.left_copy, &by_hand_polys.left_copy),
+        (&built_polys.right_copy, &by_hand_polys.right_copy),
+        (&built_polys.output_copy, &by_hand_polys.output_copy),
+    ] {
+        assert_eq!(built_poly, by_hand_poly);
+    }
+    assert_eq!(built.circuit.witness, vec![3, 4, 5, 9, 16, 8]);
+}
+
+#[test]
+fn test_builder_proof() {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::test_utils::prove_and_verify;
+
+    let circuit = build_pythagorean(constants::FIELD_17);
+    assert!(prove_and_verify(circuit.clone(), vec![3, 4, 5], &[], 3));
+}
+
+#[test]
+fn test_builder_constants_and_public_inputs() {
+    let field = Field::new(17);
+    let mut builder = CircuitBuilder::new(field);
+    let x = builder.input();
+    let seven = builder.constant(7);
+    let y = builder.public_input();
+    let sum = builder.add(x, seven);
+    builder.assert_equal(sum, y);
+    builder.mul(x, x);
+
+    // the gates are already laid out, only the witness is needed here
+    let mut circuit = builder.build();
+    circuit.circuit.assign_witness(&[3, 10]);
+
+    // x = 0, seven = 1, y = sum = 2, x^2 = 3, then the public input gate's spare slot
+    assert_eq!(circuit.circuit.witness, vec![3, 7, 10, 9, 0]);
+    assert_eq!(circuit.public_inputs(), vec![10]);
+    assert_eq!(circuit.circuit.get_constant_selectors(), vec![7, 0, 0, 0]);
+    assert_eq!(circuit.circuit.public_input_gates, vec![3]);
+}
+
+#[test]
+fn test_builder_fan_out_proof() {
+    use crate::plonk_by_hand::circuit::ConstraintError;
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::prover::ProverError;
+    use crate::plonk_by_hand::test_utils::{self, prove_and_verify};
+
+    let field = constants::FIELD_17;
+
+    // x^3 + x = y for a public y, so x is wired into four positions
+    let mut builder = CircuitBuilder::new(field);
+    let x = builder.input();
+    let y = builder.public_input();
+    let x_squared = builder.mul(x, x);
+    let x_cubed = builder.mul(x_squared, x);
+    let sum = builder.add(x_cubed, x);
+    builder.assert_equal(sum, y);
+    let circuit = builder.build();
+
+    let verify = |inputs: Vec<u64>, public_inputs: &[u64]| {
+        prove_and_verify(circuit.clone(), inputs, public_inputs, 3)
+    };
+
+    // 2^3 + 2 = 10
+    assert!(verify(vec![2, 10], &[10]));
+    assert!(!verify(vec![2, 10], &[11]));
+
+    // y shares its slot with the sum gate's output, which would overwrite the 11
+    let mut circuit = circuit.clone();
+    circuit.build_gates().unwrap();
+    circuit.circuit.assign_witness(&[2, 11]);
+    assert_eq!(
+        circuit.circuit.check_satisfied(),
+        Err(ConstraintError::Input {
+            wire: 1,
+            expected: field.element(11),
+            actual: field.element(10),
+        })
+    );
+    assert!(matches!(
+        test_utils::prove(circuit.clone(), vec![2, 11], 5).1,
+        Err(ProverError::UnsatisfiedConstraints(
+            ConstraintError::Input { .. }
+        ))
+    ));
+    assert!(!verify(vec![2, 11], &[11]));
+}
+
+#[test]
+fn test_builder_padded_proof() {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::test_utils::prove_and_verify;
+
+    let field = constants::FIELD_17;
+
+    // x^2 + x = y is three gates with the public input gate, padded to four
+    let mut builder = CircuitBuilder::new(field);
+    let x = builder.input();
+    let y = builder.public_input();
+    let x_squared = builder.mul(x, x);
+    let sum = builder.add(x_squared, x);
+    builder.assert_equal(sum, y);
+    let mut circuit = builder.build();
+    circuit.build_gates().unwrap();
+    assert_eq!(circuit.circuit.real_gate_count, 3);
+    assert_eq!(circuit.circuit.domain.size, 4);
+
+    assert!(prove_and_verify(circuit.clone(), vec![3, 12], &[12], 2));
+}
+
+#[test]
+fn test_builder_too_many_gates_for_field() {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::prover::{Prover, ProverError};
+    use crate::plonk_by_hand::setup::setup;
+    use crate::plonk_by_hand::test_utils;
+
+    // x^5 = y takes four gates and the public input gate, so eight rows
+    let mut builder = CircuitBuilder::new(constants::FIELD_17);
+    let x = builder.input();
+    let y = builder.public_input();
+    let mut power = x;
+    for _ in 0..4 {
+        power = builder.mul(power, x);
+    }
+    builder.assert_equal(power, y);
+    let circuit = builder.build();
+
+    let srs = test_utils::srs_by_hand();
+    assert_eq!(
+        circuit.clone().build_gates(),
+        Err(DomainError::NotEnoughCosets(8))
+    );
+    assert!(setup(circuit.clone(), &srs).is_err());
+    assert_eq!(
+        Prover::new(circuit, vec![2, 15], srs).err(),
+        Some(ProverError::Domain(DomainError::NotEnoughCosets(8)))
+    );
+}
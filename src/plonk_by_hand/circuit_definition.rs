@@ -0,0 +1,187 @@
+use crate::math::field::FieldElement;
+use crate::math::roots_of_unity::DomainError;
+use crate::plonk_by_hand::circuit::Circuit;
+#[cfg(test)]
+use crate::plonk_by_hand::circuit::{Gate, GateType};
+
+// A statement to prove. The definition only lays out the gates and marks the input
+// wires; the prover assigns the witness on the built Circuit.
+pub trait CircuitDefinition {
+    fn circuit(&self) -> &Circuit;
+
+    fn circuit_mut(&mut self) -> &mut Circuit;
+
+    // Inserts every gate and input, then builds the selector and copy polynomials. Fails
+    // when the field has no evaluation domain for that many gates.
+    fn build_gates(&mut self) -> Result<(), DomainError>;
+
+    // Values of the public wires, in the order they were inserted
+    fn public_inputs(&self) -> Vec<FieldElement> {
+        self.circuit().get_public_inputs()
+    }
+}
+
+// Gates as (left, right, output, type) wire indices, for trying other statements
+// through the same prover and verifier
+#[cfg(test)]
+struct GateListCircuit {
+    circuit: Circuit,
+    gates: Vec<(usize, usize, usize, GateType)>,
+    input_wires: Vec<usize>,
+    public_wires: Vec<usize>,
+}
+
+#[cfg(test)]
+impl GateListCircuit {
+    fn new(gates: Vec<(usize, usize, usize, GateType)>, input_wires: Vec<usize>) -> Self {
+        GateListCircuit::with_public_wires(gates, input_wires, Vec::new())
+    }
+
+    fn with_public_wires(
+        gates: Vec<(usize, usize, usize, GateType)>,
+        input_wires: Vec<usize>,
+        public_wires: Vec<usize>,
+    ) -> Self {
+        use crate::plonk_by_hand::constants;
+
+        GateListCircuit {
+            circuit: Circuit::new(constants::FIELD_17),
+            gates,
+            input_wires,
+            public_wires,
+        }
+    }
+}
+
+#[cfg(test)]
+impl CircuitDefinition for GateListCircuit {
+    fn circuit(&self) -> &Circuit {
+        &self.circuit
+    }
+
+    fn circuit_mut(&mut self) -> &mut Circuit {
+        &mut self.circuit
+    }
+
+    fn build_gates(&mut self) -> Result<(), DomainError> {
+        for (left_index, right_index, output_index, gate_type) in self.gates.drain(..) {
+            self.circuit.insert_gate(Gate {
+                left_index,
+                right_index,
+                output_index,
+                gate_type,
+            });
+        }
+        for wire in self.input_wires.iter() {
+            self.circuit.insert_input(*wire);
+        }
+        for wire in self.public_wires.iter() {
+            self.circuit.insert_public_input(*wire);
+        }
+
+        self.circuit.build_polynomials()
+    }
+}
+
+#[test]
+fn test_prove_other_circuit() {
+    use crate::plonk_by_hand::test_utils::prove_and_verify;
+
+    // x * y * (x + y) = d^2 with wires x = 0, y = 1, xy = 2, x + y = 3, c = 4, d = 5
+    let square_product = || {
+        GateListCircuit::new(
+            vec![
+                (0, 1, 2, GateType::Multiplication),
+                (0, 1, 3, GateType::Addition),
+                (2, 3, 4, GateType::Multiplication),
+                (5, 5, 4, GateType::Multiplication),
+            ],
+            vec![0, 1, 5],
+        )
+    };
+
+    // 2 * 5 * 7 = 70 = 2 = 6^2 in F_17
+    assert!(prove_and_verify(square_product(), vec![2, 5, 6], &[], 1));
+    // 5^2 = 8, so the last gate's output disagrees with the third's
+    assert!(!prove_and_verify(square_product(), vec![2, 5, 5], &[], 1));
+}
+
+#[test]
+fn test_prove_custom_gates() {
+    use crate::plonk_by_hand::circuit::GateSelectors;
+    use crate::plonk_by_hand::test_utils::prove_and_verify;
+
+    let field = crate::plonk_by_hand::constants::FIELD_17;
+    // 3x - 2y + xy + 7 = a, x * y = b, a + b = c, d^2 + 5 = c
+    let custom_gates = || {
+        GateListCircuit::new(
+            vec![
+                (
+                    0,
+                    1,
+                    2,
+                    GateType::Custom(GateSelectors {
+                        left: field.element(3),
+                        right: -field.element(2),
+                        output: -field.one(),
+                        multiply: field.one(),
+                        constant: field.element(7),
+                    }),
+                ),
+                (0, 1, 3, GateType::Multiplication),
+                (2, 3, 4, GateType::Addition),
+                (
+                    5,
+                    5,
+                    4,
+                    GateType::Custom(GateSelectors {
+                        left: field.zero(),
+                        right: field.zero(),
+                        output: -field.one(),
+                        multiply: field.one(),
+                        constant: field.element(5),
+                    }),
+                ),
+            ],
+            vec![0, 1, 5],
+        )
+    };
+
+    // a = 13, b = 10, c = 23 = 6 = 1 + 5
+    assert!(prove_and_verify(custom_gates(), vec![2, 5, 1], &[], 1));
+    assert!(!prove_and_verify(custom_gates(), vec![2, 5, 2], &[], 1));
+}
+
+#[test]
+fn test_prove_public_input() {
+    use crate::plonk_by_hand::test_utils::prove_and_verify;
+
+    // a^2 + b^2 = C for a public C, with wires a = 0, a^2 = 1, b = 2, b^2 = 3, C = 4
+    let public_pythagorean = || {
+        GateListCircuit::with_public_wires(
+            vec![
+                (0, 0, 1, GateType::Multiplication),
+                (2, 2, 3, GateType::Multiplication),
+                (1, 3, 4, GateType::Addition),
+            ],
+            vec![0, 2],
+            vec![4],
+        )
+    };
+
+    let mut circuit = public_pythagorean();
+    circuit.build_gates().unwrap();
+    circuit.circuit.assign_witness(&[3, 4]);
+    assert_eq!(circuit.public_inputs(), vec![8]);
+
+    // 3^2 + 4^2 = 25 = 8
+    assert!(prove_and_verify(public_pythagorean(), vec![3, 4], &[8], 1));
+    assert!(!prove_and_verify(public_pythagorean(), vec![3, 4], &[9], 1));
+    assert!(!prove_and_verify(public_pythagorean(), vec![3, 4], &[], 1));
+    assert!(!prove_and_verify(
+        public_pythagorean(),
+        vec![3, 4],
+        &[25],
+        1
+    ));
+}
@@ -1,6 +1,8 @@
-use crate::{CurvePoint, ExtensionCurvePoint, Field, PublicCoin, ECC, SRS};
+use crate::math::field::FieldElement;
+use crate::plonk_by_hand::public_coin::PublicCoin;
+use crate::{CurvePoint, ExtensionCurvePoint, Field, ECC, SRS};
 
-pub const FIELD_17: Field = Field { order: 17 };
+pub const FIELD_17: Field = Field::new(17);
 
 pub const SRS_BY_HAND: SRS = SRS {
     g_1: CurvePoint { x: 1, y: 2 },
@@ -19,20 +21,24 @@ pub const SRS_BY_HAND: SRS = SRS {
     },
 };
 
+// b1..b9 from the article, replayed through a FixedBlinding
+pub const BLINDING_BY_HAND: [FieldElement; 9] = [
+    FIELD_17.element(7),
+    FIELD_17.element(4),
+    FIELD_17.element(11),
+    FIELD_17.element(12),
+    FIELD_17.element(16),
+    FIELD_17.element(2),
+    FIELD_17.element(14),
+    FIELD_17.element(11),
+    FIELD_17.element(7),
+];
+
 pub const PUB_COIN: PublicCoin = PublicCoin {
-    b1: 7,
-    b2: 4,
-    b3: 11,
-    b4: 12,
-    b5: 16,
-    b6: 2,
-    b7: 14,
-    b8: 11,
-    b9: 7,
-    alpha: 15,
-    beta: 12,
-    gamma: 13,
-    zed: 5,
-    v: 12,
-    u: 4,
+    alpha: FIELD_17.element(15),
+    beta: FIELD_17.element(12),
+    gamma: FIELD_17.element(13),
+    zed: FIELD_17.element(5),
+    v: FIELD_17.element(12),
+    u: FIELD_17.element(4),
 };
@@ -0,0 +1,552 @@
+use crate::math::ecc::{CurvePoint, ExtensionCurvePoint, ECC};
+use crate::math::field::{Field, FieldElement};
+
+// Just enough JSON to exchange proofs, keys and SRS with other tools. Numbers are
+// unsigned integers, all these formats need; field and curve elements are hex strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    // keys in insertion order, so exports diff cleanly
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JsonError {
+    // byte offset of the first character that does not parse
+    Syntax { position: usize },
+    MissingField(String),
+    WrongType(String),
+    InvalidHex(String),
+    // a field order that is not a prime above 2
+    InvalidField(String),
+    PointNotOnCurve(String),
+    PointNotInSubgroup(String),
+    NonCanonicalScalar(String),
+    // a value that parses but disagrees with the rest of the document
+    Inconsistent(String),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            bytes: text.as_bytes(),
+            position: 0,
+        };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.position != parser.bytes.len() {
+            return Err(parser.error());
+        }
+
+        Ok(value)
+    }
+
+    // Two-space indentation. Containers holding only scalars stay on one line, so
+    // each point is a single line in a diff.
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Result<&Json, JsonError> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or_else(|| JsonError::MissingField(key.to_string())),
+            _ => Err(JsonError::WrongType(key.to_string())),
+        }
+    }
+
+    pub fn as_array(&self, name: &str) -> Result<&[Json], JsonError> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(JsonError::WrongType(name.to_string())),
+        }
+    }
+
+    pub fn as_u64(&self, name: &str) -> Result<u64, JsonError> {
+        match self {
+            Json::Number(value) => Ok(*value),
+            _ => Err(JsonError::WrongType(name.to_string())),
+        }
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, Json::Array(_) | Json::Object(_))
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => out.push_str(&value.to_string()),
+            Json::String(value) => write_string(out, value),
+            Json::Array(values) => {
+                let items: Vec<(Option<&str>, &Json)> =
+                    values.iter().map(|value| (None, value)).collect();
+                write_container(out, indent, ('[', ']'), &items);
+            }
+            Json::Object(fields) => {
+                let items: Vec<(Option<&str>, &Json)> = fields
+                    .iter()
+                    .map(|(key, value)| (Some(key.as_str()), value))
+                    .collect();
+                write_container(out, indent, ('{', '}'), &items);
+            }
+        }
+    }
+}
+
+fn write_container(
+    out: &mut String,
+    indent: usize,
+    (open, close): (char, char),
+    items: &[(Option<&str>, &Json)],
+) {
+    if items.is_empty() {
+        out.push(open);
+        out.push(close);
+        return;
+    }
+
+    let inline = items.iter().all(|(_, value)| !value.is_container());
+    out.push(open);
+    for (i, (key, value)) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if inline {
+            out.push(' ');
+        } else {
+            out.push('\n');
+            out.push_str(&"  ".repeat(indent + 1));
+        }
+        if let Some(key) = key {
+            write_string(out, key);
+            out.push_str(": ");
+        }
+        value.write(out, indent + 1);
+    }
+    if inline {
+        out.push(' ');
+    } else {
+        out.push('\n');
+        out.push_str(&"  ".repeat(indent));
+    }
+    out.push(close);
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> JsonError {
+        JsonError::Syntax {
+            position: self.position,
+        }
+    }
+
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.bytes.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        self.whitespace();
+        if self.bytes.get(self.position) != Some(&byte) {
+            return Err(self.error());
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        if !self.bytes[self.position..].starts_with(word.as_bytes()) {
+            return Err(self.error());
+        }
+        self.position += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.whitespace();
+        match self.bytes.get(self.position) {
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'0'..=b'9') => self.number(),
+            Some(b'[') => self.array(),
+            Some(b'{') => self.object(),
+            _ => Err(self.error()),
+        }
+    }
+
+    // Unsigned integers without leading zeros
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.bytes.get(self.position) {
+            self.position += 1;
+        }
+        let digits = &self.bytes[start..self.position];
+        if digits.len() > 1 && digits[0] == b'0' {
+            return Err(JsonError::Syntax { position: start });
+        }
+
+        std::str::from_utf8(digits)
+            .unwrap()
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| JsonError::Syntax { position: start })
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = *self.bytes.get(self.position).ok_or_else(|| self.error())?;
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.bytes.get(self.position) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'u') => {
+                            let hex = self
+                                .bytes
+                                .get(self.position + 1..self.position + 5)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error())?;
+                            self.position += 4;
+                            hex
+                        }
+                        _ => return Err(self.error()),
+                    };
+                    self.position += 1;
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte if byte < 0x20 => return Err(self.error()),
+                byte => bytes.push(byte),
+            }
+        }
+
+        // the input was a &str, so the unescaped bytes are still UTF-8
+        Ok(String::from_utf8(bytes).unwrap())
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.whitespace();
+        if self.bytes.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.whitespace();
+            match self.bytes.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'{')?;
+        let mut fields: Vec<(String, Json)> = Vec::new();
+        self.whitespace();
+        if self.bytes.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.whitespace();
+            let key_position = self.position;
+            let key = self.string()?;
+            // a repeated key would make get() ambiguous
+            if fields.iter().any(|(name, _)| *name == key) {
+                return Err(JsonError::Syntax {
+                    position: key_position,
+                });
+            }
+            self.expect(b':')?;
+            fields.push((key, self.value()?));
+            self.whitespace();
+            match self.bytes.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+}
+
+// "0x" followed by lowercase hex digits without leading zeros, the one spelling
+// from_hex accepts
+pub fn hex(value: u64) -> Json {
+    Json::String(format!("{:#x}", value))
+}
+
+pub fn from_hex(json: &Json, name: &str) -> Result<u64, JsonError> {
+    let Json::String(text) = json else {
+        return Err(JsonError::WrongType(name.to_string()));
+    };
+    let invalid = || JsonError::InvalidHex(name.to_string());
+
+    let digits = text.strip_prefix("0x").ok_or_else(invalid)?;
+    let canonical = !digits.is_empty()
+        && (digits == "0" || !digits.starts_with('0'))
+        && digits
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    if !canonical {
+        return Err(invalid());
+    }
+
+    u64::from_str_radix(digits, 16).map_err(|_| invalid())
+}
+
+// Everything downstream divides by 2 and takes inverses, so the order must be an odd prime
+pub fn field_from_json(json: &Json, name: &str) -> Result<Field, JsonError> {
+    let field = Field::new(from_hex(json, name)?);
+    if field.order <= 2 || !field.is_prime_order() {
+        return Err(JsonError::InvalidField(name.to_string()));
+    }
+
+    Ok(field)
+}
+
+pub fn scalar_to_json(scalar: FieldElement) -> Json {
+    hex(scalar.value)
+}
+
+pub fn scalar_from_json(json: &Json, name: &str, field: Field) -> Result<FieldElement, JsonError> {
+    let value = from_hex(json, name)?;
+    if value >= field.order {
+        return Err(JsonError::NonCanonicalScalar(name.to_string()));
+    }
+
+    Ok(field.element(value))
+}
+
+// The point at infinity is null
+pub fn point_to_json(point: &CurvePoint) -> Json {
+    if CurvePoint::equals(point, &CurvePoint::point_at_infinity()) {
+        return Json::Null;
+    }
+
+    Json::object(vec![("x", hex(point.x)), ("y", hex(point.y))])
+}
+
+// Only points in the subgroup of the given order, which the pairing check can handle
+pub fn point_from_json(
+    json: &Json,
+    name: &str,
+    ecc: &ECC,
+    order: u64,
+) -> Result<CurvePoint, JsonError> {
+    if *json == Json::Null {
+        return Ok(CurvePoint::point_at_infinity());
+    }
+
+    let point = CurvePoint::new(
+        from_hex(json.get("x")?, name)?,
+        from_hex(json.get("y")?, name)?,
+    );
+    if !ecc.is_on_curve(&point) {
+        return Err(JsonError::PointNotOnCurve(name.to_string()));
+    }
+    if !ecc.in_subgroup(&point, order) {
+        return Err(JsonError::PointNotInSubgroup(name.to_string()));
+    }
+
+    Ok(point)
+}
+
+// x + y * u, where u is the extension field's square root
+pub fn extension_point_to_json(point: &ExtensionCurvePoint) -> Json {
+    Json::object(vec![
+        ("x", hex(point.x)),
+        ("y", hex(point.y)),
+        ("u", Json::Bool(point.u)),
+    ])
+}
+
+pub fn extension_point_from_json(
+    json: &Json,
+    name: &str,
+    ecc: &ECC,
+) -> Result<ExtensionCurvePoint, JsonError> {
+    let x = from_hex(json.get("x")?, name)?;
+    let y = from_hex(json.get("y")?, name)?;
+    let Json::Bool(u) = json.get("u")? else {
+        return Err(JsonError::WrongType(name.to_string()));
+    };
+    let point = ExtensionCurvePoint::new(x, y, *u);
+    if !ecc.is_on_extension_curve(&point) {
+        return Err(JsonError::PointNotOnCurve(name.to_string()));
+    }
+
+    Ok(point)
+}
+
+#[test]
+fn test_json_round_trip() {
+    let json = Json::object(vec![
+        ("name", Json::String("a \"quoted\"\n\\ name".to_string())),
+        ("flags", Json::Array(vec![Json::Bool(true), Json::Null])),
+        ("count", Json::Number(u64::MAX)),
+        ("empty", Json::Array(Vec::new())),
+        (
+            "nested",
+            Json::object(vec![("points", Json::Array(vec![hex(255), hex(0)]))]),
+        ),
+    ]);
+
+    let text = json.to_pretty_string();
+    assert_eq!(
+        text,
+        "{\n  \"name\": \"a \\\"quoted\\\"\\n\\\\ name\",\n  \"flags\": [ true, null ],\n  \
+         \"count\": 18446744073709551615,\n  \"empty\": [],\n  \"nested\": {\n    \
+         \"points\": [ \"0xff\", \"0x0\" ]\n  }\n}\n"
+    );
+    assert_eq!(Json::parse(&text), Ok(json));
+    assert_eq!(
+        Json::parse(r#" { "a" : [1, "A\/"] } "#),
+        Ok(Json::object(vec![(
+            "a",
+            Json::Array(vec![Json::Number(1), Json::String("A/".to_string())])
+        )]))
+    );
+}
+
+#[test]
+fn test_json_rejects() {
+    for (text, position) in [
+        ("", 0),
+        ("[1,]", 3),
+        ("{\"a\": 1, \"a\": 2}", 9),
+        ("01", 0),
+        ("-1", 0),
+        ("1.5", 1),
+        ("18446744073709551616", 0),
+        ("\"open", 5),
+        ("[1] [2]", 4),
+        ("nul", 0),
+    ] {
+        assert_eq!(
+            Json::parse(text),
+            Err(JsonError::Syntax { position }),
+            "{}",
+            text
+        );
+    }
+
+    let field = Field::new(17);
+    for text in ["0x", "11", "0x011", "0xG", "0xA"] {
+        assert_eq!(
+            scalar_from_json(&Json::String(text.to_string()), "s", field),
+            Err(JsonError::InvalidHex("s".to_string()))
+        );
+    }
+    assert_eq!(
+        scalar_from_json(&hex(17), "s", field),
+        Err(JsonError::NonCanonicalScalar("s".to_string()))
+    );
+    assert_eq!(
+        scalar_from_json(&Json::Number(3), "s", field),
+        Err(JsonError::WrongType("s".to_string()))
+    );
+
+    let ecc = ECC {
+        field: Field::new(101),
+    };
+    let off_curve = Json::object(vec![("x", hex(1)), ("y", hex(1))]);
+    assert_eq!(
+        point_from_json(&off_curve, "p", &ecc, 17).err(),
+        Some(JsonError::PointNotOnCurve("p".to_string()))
+    );
+    assert_eq!(
+        point_from_json(&Json::object(vec![("x", hex(1))]), "p", &ecc, 17).err(),
+        Some(JsonError::MissingField("y".to_string()))
+    );
+    // (48, 0) is on the curve but has order 2
+    let order_2 = Json::object(vec![("x", hex(48)), ("y", hex(0))]);
+    assert_eq!(
+        point_from_json(&order_2, "p", &ecc, 17).err(),
+        Some(JsonError::PointNotInSubgroup("p".to_string()))
+    );
+    let generator = Json::object(vec![("x", hex(1)), ("y", hex(2))]);
+    assert!(point_from_json(&generator, "p", &ecc, 17).is_ok());
+
+    let extension_point =
+        |y| Json::object(vec![("x", hex(36)), ("y", hex(y)), ("u", Json::Bool(true))]);
+    assert!(extension_point_from_json(&extension_point(31), "q", &ecc).is_ok());
+    assert_eq!(
+        extension_point_from_json(&extension_point(32), "q", &ecc).err(),
+        Some(JsonError::PointNotOnCurve("q".to_string()))
+    );
+
+    for order in [0, 1, 2, 15] {
+        assert_eq!(
+            field_from_json(&hex(order), "f"),
+            Err(JsonError::InvalidField("f".to_string()))
+        );
+    }
+    assert_eq!(field_from_json(&hex(17), "f"), Ok(Field::new(17)));
+}
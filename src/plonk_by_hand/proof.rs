@@ -1,7 +1,38 @@
-use crate::math::ecc::CurvePoint;
+use crate::math::ecc::{CurvePoint, ECC};
+use crate::math::field::{Field, FieldElement};
 use crate::math::polynomial::Polynomial;
+use crate::plonk_by_hand::json::{
+    point_from_json, point_to_json, scalar_from_json, scalar_to_json, Json, JsonError,
+};
 
-#[derive(Default)]
+// Version 1: the version byte, then the nine commitments as 9-byte compressed points
+// and the seven openings as 8-byte big endian scalars, in declaration order
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+pub const PROOF_BYTES: usize = 1 + 9 * POINT_BYTES + 7 * SCALAR_BYTES;
+
+const POINT_BYTES: usize = 9;
+const SCALAR_BYTES: usize = 8;
+
+// SEC1-style point tags. The point at infinity is the tag with x = 0.
+const TAG_INFINITY: u8 = 0x00;
+const TAG_EVEN_Y: u8 = 0x02;
+const TAG_ODD_Y: u8 = 0x03;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProofDecodingError {
+    WrongLength { expected: usize, actual: usize },
+    UnsupportedVersion(u8),
+    // an unknown tag, or the point at infinity with a nonzero x
+    NonCanonicalPoint { name: &'static str },
+    // x is not the x-coordinate of a point with y of the tagged parity
+    PointNotOnCurve { name: &'static str, x: u64 },
+    // a point on the curve outside the subgroup of the scalar field's order
+    PointNotInSubgroup { name: &'static str, x: u64 },
+    // a scalar at or above the field order, which has a smaller encoding
+    NonCanonicalScalar { name: &'static str, value: u64 },
+}
+
+#[derive(Default, Clone)]
 pub struct Proof {
     pub a: CurvePoint,
     pub b: CurvePoint,
@@ -12,13 +43,180 @@ pub struct Proof {
     pub t_hi: CurvePoint,
     pub w: CurvePoint,
     pub wz: CurvePoint,
-    pub a_bar: u32,
-    pub b_bar: u32,
-    pub c_bar: u32,
-    pub left_copy_bar: u32,
-    pub right_copy_bar: u32,
-    pub r_bar: u32,
-    pub z_bar: u32,
+    pub a_bar: FieldElement,
+    pub b_bar: FieldElement,
+    pub c_bar: FieldElement,
+    pub left_copy_bar: FieldElement,
+    pub right_copy_bar: FieldElement,
+    pub r_bar: FieldElement,
+    pub z_bar: FieldElement,
+}
+
+impl Proof {
+    pub fn commitments(&self) -> [(&'static str, &CurvePoint); 9] {
+        [
+            ("a", &self.a),
+            ("b", &self.b),
+            ("c", &self.c),
+            ("z", &self.z),
+            ("t_lo", &self.t_lo),
+            ("t_mid", &self.t_mid),
+            ("t_hi", &self.t_hi),
+            ("w", &self.w),
+            ("wz", &self.wz),
+        ]
+    }
+
+    pub fn openings(&self) -> [(&'static str, FieldElement); 7] {
+        [
+            ("a_bar", self.a_bar),
+            ("b_bar", self.b_bar),
+            ("c_bar", self.c_bar),
+            ("left_copy_bar", self.left_copy_bar),
+            ("right_copy_bar", self.right_copy_bar),
+            ("r_bar", self.r_bar),
+            ("z_bar", self.z_bar),
+        ]
+    }
+
+    // Canonical for proofs whose points are on the curve, which from_bytes checks
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PROOF_BYTES);
+        bytes.push(PROOF_FORMAT_VERSION);
+
+        for (_, point) in self.commitments() {
+            if CurvePoint::equals(point, &CurvePoint::point_at_infinity()) {
+                bytes.push(TAG_INFINITY);
+                bytes.extend_from_slice(&0u64.to_be_bytes());
+            } else {
+                bytes.push(if point.y % 2 == 1 {
+                    TAG_ODD_Y
+                } else {
+                    TAG_EVEN_Y
+                });
+                bytes.extend_from_slice(&point.x.to_be_bytes());
+            }
+        }
+        for (_, opening) in self.openings() {
+            bytes.extend_from_slice(&opening.value.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    // Accepts exactly the bytes to_bytes produces for a valid proof over this curve
+    // and scalar field
+    pub fn from_bytes(bytes: &[u8], ecc: &ECC, field: Field) -> Result<Proof, ProofDecodingError> {
+        if bytes.len() != PROOF_BYTES {
+            return Err(ProofDecodingError::WrongLength {
+                expected: PROOF_BYTES,
+                actual: bytes.len(),
+            });
+        }
+        if bytes[0] != PROOF_FORMAT_VERSION {
+            return Err(ProofDecodingError::UnsupportedVersion(bytes[0]));
+        }
+
+        let proof = Proof::default();
+        let mut offset = 1;
+        let read_u64 =
+            |offset: usize| u64::from_be_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+        let mut points = Vec::with_capacity(9);
+        for (name, _) in proof.commitments() {
+            let x = read_u64(offset + 1);
+            let point = match bytes[offset] {
+                TAG_INFINITY if x == 0 => CurvePoint::point_at_infinity(),
+                tag @ (TAG_EVEN_Y | TAG_ODD_Y) => {
+                    let y_is_odd = tag == TAG_ODD_Y;
+                    // y = 0 has no odd twin, so point_from_x would hand back the even root
+                    let point = match ecc.point_from_x(x, y_is_odd) {
+                        Some(point) if (point.y % 2 == 1) == y_is_odd => point,
+                        _ => return Err(ProofDecodingError::PointNotOnCurve { name, x }),
+                    };
+                    if !ecc.in_subgroup(&point, field.order) {
+                        return Err(ProofDecodingError::PointNotInSubgroup { name, x });
+                    }
+                    point
+                }
+                _ => return Err(ProofDecodingError::NonCanonicalPoint { name }),
+            };
+            points.push(point);
+            offset += POINT_BYTES;
+        }
+
+        let mut scalars = Vec::with_capacity(7);
+        for (name, _) in proof.openings() {
+            let value = read_u64(offset);
+            if value >= field.order {
+                return Err(ProofDecodingError::NonCanonicalScalar { name, value });
+            }
+            scalars.push(field.element(value));
+            offset += SCALAR_BYTES;
+        }
+
+        Ok(Proof::from_parts(points, scalars))
+    }
+
+    // One key per commitment and opening, named as in the struct
+    pub fn to_json(&self) -> String {
+        let mut fields: Vec<(&str, Json)> = self
+            .commitments()
+            .iter()
+            .map(|(name, point)| (*name, point_to_json(point)))
+            .collect();
+        fields.extend(
+            self.openings()
+                .iter()
+                .map(|(name, opening)| (*name, scalar_to_json(*opening))),
+        );
+
+        Json::object(fields).to_pretty_string()
+    }
+
+    // As strict as from_bytes: points must be on the curve, scalars below the order
+    pub fn from_json(text: &str, ecc: &ECC, field: Field) -> Result<Proof, JsonError> {
+        let json = Json::parse(text)?;
+        let proof = Proof::default();
+
+        let mut points = Vec::with_capacity(9);
+        for (name, _) in proof.commitments() {
+            points.push(point_from_json(json.get(name)?, name, ecc, field.order)?);
+        }
+        let mut scalars = Vec::with_capacity(7);
+        for (name, _) in proof.openings() {
+            scalars.push(scalar_from_json(json.get(name)?, name, field)?);
+        }
+
+        Ok(Proof::from_parts(points, scalars))
+    }
+
+    // The values in the order commitments() and openings() list them
+    fn from_parts(points: Vec<CurvePoint>, scalars: Vec<FieldElement>) -> Proof {
+        let mut proof = Proof::default();
+        [
+            proof.a,
+            proof.b,
+            proof.c,
+            proof.z,
+            proof.t_lo,
+            proof.t_mid,
+            proof.t_hi,
+            proof.w,
+            proof.wz,
+        ] = points.try_into().unwrap();
+        [
+            proof.a_bar,
+            proof.b_bar,
+            proof.c_bar,
+            proof.left_copy_bar,
+            proof.right_copy_bar,
+            proof.r_bar,
+            proof.z_bar,
+        ] = scalars.try_into().unwrap();
+
+        proof
+    }
 }
 
 #[derive(Default)]
@@ -38,12 +236,160 @@ pub struct ProverPolys {
 
 #[derive(Default, Clone)]
 pub struct OpeningEvals {
-    pub a: u32,
-    pub b: u32,
-    pub c: u32,
-    pub left_copy: u32,
-    pub right_copy: u32,
-    pub t: u32,
-    pub z: u32,
-    pub r: u32,
+    pub a: FieldElement,
+    pub b: FieldElement,
+    pub c: FieldElement,
+    pub left_copy: FieldElement,
+    pub right_copy: FieldElement,
+    pub t: FieldElement,
+    pub z: FieldElement,
+    pub r: FieldElement,
+}
+
+// The same statement and setup as main.rs, with seeded blinding
+#[cfg(test)]
+fn test_proof_and_key() -> (Proof, crate::plonk_by_hand::verifier::VerifyingKey) {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::pythagorean_circuit_builder::PythagoreanCircuit;
+    use crate::plonk_by_hand::test_utils;
+
+    let (vk, proof) = test_utils::prove(
+        PythagoreanCircuit::new(constants::FIELD_17),
+        vec![3, 4, 5],
+        3,
+    );
+    (proof.unwrap(), vk)
+}
+
+#[test]
+fn test_proof_bytes_round_trip() {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::verifier::verify;
+
+    let ecc = constants::SRS_BY_HAND.ecc;
+    let field = constants::FIELD_17;
+    let (proof, vk) = test_proof_and_key();
+
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), PROOF_BYTES);
+    assert_eq!(bytes[0], PROOF_FORMAT_VERSION);
+
+    let decoded = Proof::from_bytes(&bytes, &ecc, field).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(verify(&vk, &[], &decoded), Ok(()));
+
+    let mut with_infinity = decoded.clone();
+    with_infinity.w = CurvePoint::point_at_infinity();
+    let bytes = with_infinity.to_bytes();
+    let decoded = Proof::from_bytes(&bytes, &ecc, field).unwrap();
+    assert!(CurvePoint::equals(
+        &decoded.w,
+        &CurvePoint::point_at_infinity()
+    ));
+    assert_eq!(decoded.to_bytes(), bytes);
+}
+
+#[test]
+fn test_proof_bytes_strict_decoding() {
+    use crate::plonk_by_hand::constants;
+
+    let ecc = constants::SRS_BY_HAND.ecc;
+    let field = constants::FIELD_17;
+    let bytes = test_proof_and_key().0.to_bytes();
+    let decode = |edit: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = bytes.clone();
+        edit(&mut bytes);
+        Proof::from_bytes(&bytes, &ecc, field).err()
+    };
+
+    assert_eq!(
+        decode(&|bytes| bytes.truncate(PROOF_BYTES - 1)),
+        Some(ProofDecodingError::WrongLength {
+            expected: PROOF_BYTES,
+            actual: PROOF_BYTES - 1
+        })
+    );
+    assert_eq!(
+        decode(&|bytes| bytes[0] = 2),
+        Some(ProofDecodingError::UnsupportedVersion(2))
+    );
+
+    // the commitment to a starts at byte 1, b at byte 10
+    assert_eq!(
+        decode(&|bytes| bytes[1] = 0x04),
+        Some(ProofDecodingError::NonCanonicalPoint { name: "a" })
+    );
+    assert_eq!(
+        decode(&|bytes| bytes[1..10].copy_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 1])),
+        Some(ProofDecodingError::NonCanonicalPoint { name: "a" })
+    );
+
+    let off_curve = (0..101)
+        .find(|x| ecc.point_from_x(*x, false).is_none())
+        .unwrap();
+    let mut encoded = vec![TAG_EVEN_Y];
+    encoded.extend_from_slice(&off_curve.to_be_bytes());
+    assert_eq!(
+        decode(&|bytes| bytes[10..19].copy_from_slice(&encoded)),
+        Some(ProofDecodingError::PointNotOnCurve {
+            name: "b",
+            x: off_curve
+        })
+    );
+    encoded[1..].copy_from_slice(&101u64.to_be_bytes());
+    assert_eq!(
+        decode(&|bytes| bytes[10..19].copy_from_slice(&encoded)),
+        Some(ProofDecodingError::PointNotOnCurve { name: "b", x: 101 })
+    );
+
+    // (48, 0) has order 2 and (27, 30) has order 3, neither is in the order 17 subgroup
+    for x in [48u64, 27] {
+        encoded[1..].copy_from_slice(&x.to_be_bytes());
+        assert_eq!(
+            decode(&|bytes| bytes[10..19].copy_from_slice(&encoded)),
+            Some(ProofDecodingError::PointNotInSubgroup { name: "b", x })
+        );
+    }
+
+    // r_bar is the sixth scalar, after the 81 bytes of points
+    let r_bar = 1 + 9 * POINT_BYTES + 5 * SCALAR_BYTES;
+    assert_eq!(
+        decode(&|bytes| bytes[r_bar..r_bar + 8].copy_from_slice(&17u64.to_be_bytes())),
+        Some(ProofDecodingError::NonCanonicalScalar {
+            name: "r_bar",
+            value: 17
+        })
+    );
+}
+
+#[test]
+fn test_proof_json_round_trip() {
+    use crate::plonk_by_hand::constants;
+    use crate::plonk_by_hand::verifier::verify;
+
+    let ecc = constants::SRS_BY_HAND.ecc;
+    let field = constants::FIELD_17;
+    let (mut proof, vk) = test_proof_and_key();
+    proof.w = CurvePoint::point_at_infinity();
+
+    let text = proof.to_json();
+    assert!(text.contains("\"w\": null"));
+    let parsed = Proof::from_json(&text, &ecc, field).unwrap();
+    assert_eq!(parsed.to_json(), text);
+    assert_eq!(parsed.to_bytes(), proof.to_bytes());
+
+    let (proof, _) = test_proof_and_key();
+    let parsed = Proof::from_json(&proof.to_json(), &ecc, field).unwrap();
+    assert_eq!(verify(&vk, &[], &parsed), Ok(()));
+
+    let z_bar = format!("\"z_bar\": \"{:#x}\"", proof.z_bar.value);
+    let text = proof.to_json();
+    assert_eq!(
+        Proof::from_json(&text.replace(&z_bar, "\"z_bar\": \"0x11\""), &ecc, field).err(),
+        Some(JsonError::NonCanonicalScalar("z_bar".to_string()))
+    );
+    assert_eq!(
+        Proof::from_json(&text.replace(&z_bar, "\"zbar\": \"0x1\""), &ecc, field).err(),
+        Some(JsonError::MissingField("z_bar".to_string()))
+    );
 }
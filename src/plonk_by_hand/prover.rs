@@ -1,64 +1,194 @@
-use crate::field::Field;
-use crate::math::ecc::{CurvePoint, ECC};
+use crate::field::{Field, FieldElement};
+use crate::math::ecc::CurvePoint;
+use crate::math::evaluations::Evaluations;
 use crate::math::polynomial::Polynomial;
+use crate::math::roots_of_unity::DomainError;
+#[cfg(test)]
+use crate::plonk_by_hand::blinding::FixedBlinding;
+use crate::plonk_by_hand::blinding::{BlindingRng, OsRng};
+use crate::plonk_by_hand::circuit::{Circuit, ConstraintError};
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
+#[cfg(test)]
 use crate::plonk_by_hand::constants;
 use crate::plonk_by_hand::proof::{OpeningEvals, Proof, ProverPolys};
 use crate::plonk_by_hand::public_coin::PublicCoin;
+#[cfg(test)]
+use crate::plonk_by_hand::setup::setup;
+use crate::plonk_by_hand::setup::{CosetEvaluations, ProvingKey};
 use crate::plonk_by_hand::structured_reference_string::SRS;
+#[cfg(test)]
+use crate::plonk_by_hand::test_utils;
+use crate::plonk_by_hand::transcript::Transcript;
+use crate::plonk_by_hand::verifier::VerifyingKey;
+#[cfg(test)]
 use crate::PythagoreanCircuit;
 
-// TODO: make generic to any circuit, make circuit template/interface
 pub struct Prover {
-    py_circuit: PythagoreanCircuit,
+    circuit: Circuit,
+    // only for starting the transcript the verifier replays
+    vk: VerifyingKey,
     srs: SRS,
-    ecc: ECC,
     field: Field,
     prover_polys: ProverPolys,
     pub opening_evals: OpeningEvals,
     pub_coin: PublicCoin,
+    fixed_challenges: bool,
+    coset: Option<CosetEvaluations>,
+    rng: Box<dyn BlindingRng>,
+    // b1..b9, private to the prover
+    blinding: [FieldElement; 9],
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProverError {
+    // the witness fails a gate or copy constraint, found before any commitment
+    UnsatisfiedConstraints(ConstraintError),
+    // t_z does not vanish on the domain, so there is no quotient polynomial t
+    NonzeroQuotientRemainder,
+    // committing a polynomial of this degree needs degree + 1 points in G1
+    SrsTooSmall { degree: usize, points: usize },
+    // beta and gamma zeroed an accumulator denominator on every attempt
+    NoUsableChallenges,
+    // the circuit has this many input wires, and each needs exactly one value
+    WrongInputCount { expected: usize, actual: usize },
+    // the field has no evaluation domain that fits the gates
+    Domain(DomainError),
+    // the blinding rng could not produce a value, e.g. no OS entropy source
+    EntropyUnavailable,
+}
+
+// With Fiat-Shamir, fresh blinding gives fresh beta and gamma, so an unlucky pair that
+// zeroes an accumulator denominator is retried. In F_17 about half the pairs are unlucky.
+const MAX_PROOF_ATTEMPTS: usize = 32;
+
+// z has the highest degree of the committed polynomials, n + 2
+fn check_srs_size(n: usize, srs: &SRS) -> Result<(), ProverError> {
+    let degree = n + 2;
+    let points = srs.g_1_points.len();
+    if points <= degree {
+        return Err(ProverError::SrsTooSmall { degree, points });
+    }
+    Ok(())
 }
 
 impl Prover {
-    pub fn new(field: Field, inputs: Vec<u32>, srs: SRS) -> Prover {
-        let mut py_circuit = PythagoreanCircuit::new(field.clone());
-        py_circuit.build_circuit_with_inputs(inputs);
-        let ecc = ECC {
-            field: Field { order: 101 },
-        };
+    pub fn new<C: CircuitDefinition>(
+        mut circuit: C,
+        inputs: Vec<u64>,
+        srs: SRS,
+    ) -> Result<Prover, ProverError> {
+        circuit.build_gates().map_err(ProverError::Domain)?;
+        check_srs_size(circuit.circuit().domain.size, &srs)?;
+        let pk = ProvingKey::new(circuit.circuit().clone(), &srs);
+        Prover::from_key(&pk, inputs, srs)
+    }
 
-        Prover {
-            py_circuit,
+    // The gates, input wires and fixed polynomials all come from the key, so nothing is
+    // built again and the witness always fits the key's circuit
+    pub fn from_key(pk: &ProvingKey, inputs: Vec<u64>, srs: SRS) -> Result<Prover, ProverError> {
+        let mut circuit = pk.circuit.clone();
+        circuit
+            .assign_witness(&inputs)
+            .ok_or(ProverError::WrongInputCount {
+                expected: circuit.input_wires.len(),
+                actual: inputs.len(),
+            })?;
+
+        // private polynomials
+        circuit.build_polynomials_with_input();
+        let field = circuit.field;
+
+        Ok(Prover {
+            circuit,
+            vk: pk.vk.clone(),
             srs,
-            ecc,
             field,
             prover_polys: Default::default(),
             opening_evals: Default::default(),
             pub_coin: Default::default(),
+            fixed_challenges: false,
+            coset: pk.coset.clone(),
+            rng: Box::new(OsRng::new()),
+            blinding: [field.zero(); 9],
+        })
+    }
+
+    pub fn generate_proof(&mut self) -> Result<Proof, ProverError> {
+        self.circuit
+            .check_satisfied()
+            .map_err(ProverError::UnsatisfiedConstraints)?;
+        check_srs_size(self.circuit.domain.size, &self.srs)?;
+
+        for _ in 0..MAX_PROOF_ATTEMPTS {
+            self.draw_blinding_factors()?;
+            match self.try_generate_proof() {
+                // fixed challenges come out the same on every attempt
+                Err(ProverError::NoUsableChallenges) if !self.fixed_challenges => continue,
+                result => return result,
+            }
         }
+
+        Err(ProverError::NoUsableChallenges)
     }
 
-    pub fn generate_proof(&mut self) -> Proof {
-        self.set_wire_polys();
-        self.set_z_poly();
-        self.set_t_polys();
+    // Each round's commitments go into the transcript before the next challenge is
+    // squeezed, in the same order the verifier replays them
+    fn try_generate_proof(&mut self) -> Result<Proof, ProverError> {
+        let mut transcript = self.new_transcript();
 
+        // Round 1
+        self.set_wire_polys();
+        let a = self.commit_poly(&self.prover_polys.a);
+        let b = self.commit_poly(&self.prover_polys.b);
+        let c = self.commit_poly(&self.prover_polys.c);
+        transcript.append_point(b"a", &a);
+        transcript.append_point(b"b", &b);
+        transcript.append_point(b"c", &c);
+        self.pub_coin.beta = transcript.challenge_scalar("beta");
+        self.pub_coin.gamma = transcript.challenge_scalar("gamma");
+
+        // Round 2
+        self.set_z_poly().ok_or(ProverError::NoUsableChallenges)?;
+        let z = self.commit_poly(&self.prover_polys.z);
+        transcript.append_point(b"z", &z);
+        self.pub_coin.alpha = transcript.challenge_scalar("alpha");
+
+        // Round 3
+        self.set_t_polys()?;
+        let t_lo = self.commit_poly(&self.prover_polys.t_lo);
+        let t_mid = self.commit_poly(&self.prover_polys.t_mid);
+        let t_hi = self.commit_poly(&self.prover_polys.t_hi);
+        transcript.append_point(b"t_lo", &t_lo);
+        transcript.append_point(b"t_mid", &t_mid);
+        transcript.append_point(b"t_hi", &t_hi);
+        self.pub_coin.zed = transcript.challenge_outside_domain("zeta", &self.circuit.domain);
+
+        // Round 4
         self.set_first_opening_evals();
-
         self.set_r_poly();
-
         self.set_r_opening_eval();
-
+        let evals = &self.opening_evals;
+        transcript.append_scalar(b"a_bar", evals.a);
+        transcript.append_scalar(b"b_bar", evals.b);
+        transcript.append_scalar(b"c_bar", evals.c);
+        transcript.append_scalar(b"left_copy_bar", evals.left_copy);
+        transcript.append_scalar(b"right_copy_bar", evals.right_copy);
+        transcript.append_scalar(b"r_bar", evals.r);
+        transcript.append_scalar(b"z_bar", evals.z);
+        self.pub_coin.v = transcript.challenge_scalar("v");
+
+        // Round 5, only the verifier needs the last challenge u
         self.set_w_poly();
         self.set_wz_poly();
 
-        Proof {
-            a: self.commit_poly(&self.prover_polys.a),
-            b: self.commit_poly(&self.prover_polys.b),
-            c: self.commit_poly(&self.prover_polys.c),
-            z: self.commit_poly(&self.prover_polys.z),
-            t_lo: self.commit_poly(&self.prover_polys.t_lo),
-            t_mid: self.commit_poly(&self.prover_polys.t_mid),
-            t_hi: self.commit_poly(&self.prover_polys.t_hi),
+        Ok(Proof {
+            a,
+            b,
+            c,
+            z,
+            t_lo,
+            t_mid,
+            t_hi,
             w: self.commit_poly(&self.prover_polys.w),
             wz: self.commit_poly(&self.prover_polys.wz),
             a_bar: self.opening_evals.a,
@@ -68,80 +198,85 @@ impl Prover {
             right_copy_bar: self.opening_evals.right_copy,
             r_bar: self.opening_evals.r,
             z_bar: self.opening_evals.z,
-        }
+        })
     }
 
+    // Replays the given challenges instead of deriving them from the transcript, as
+    // the plonk_by_hand article does. Not sound, so tests only.
+    #[cfg(test)]
     pub fn set_public_coin(&mut self, public_coin: PublicCoin) {
+        // a coin left at its default has zero challenges outside any field
+        let coin = &public_coin;
+        assert!(
+            [coin.alpha, coin.beta, coin.gamma, coin.zed, coin.v, coin.u]
+                .iter()
+                .all(|challenge| challenge.field == self.field && !challenge.is_zero()),
+            "every fixed challenge must be a non-zero element of the circuit's field"
+        );
         self.pub_coin = public_coin;
+        self.fixed_challenges = true;
     }
 
-    fn set_wire_polys(&mut self) {
-        self.prover_polys.a = self.get_blinded_wire_poly(
-            &self.py_circuit.circuit.circuit_polys.left,
-            self.pub_coin.b2,
-            self.pub_coin.b1,
-        );
-
-        self.prover_polys.b = self.get_blinded_wire_poly(
-            &self.py_circuit.circuit.circuit_polys.right,
-            self.pub_coin.b4,
-            self.pub_coin.b3,
-        );
+    // Where b1..b9 come from. Defaults to OS entropy; seed it or use a FixedBlinding
+    // for reproducible proofs.
+    pub fn set_blinding_rng(&mut self, rng: Box<dyn BlindingRng>) {
+        self.rng = rng;
+    }
 
-        self.prover_polys.c = self.get_blinded_wire_poly(
-            &self.py_circuit.circuit.circuit_polys.output,
-            self.pub_coin.b6,
-            self.pub_coin.b5,
-        );
+    fn draw_blinding_factors(&mut self) -> Result<(), ProverError> {
+        for b in self.blinding.iter_mut() {
+            *b = self
+                .rng
+                .next_scalar(self.field)
+                .ok_or(ProverError::EntropyUnavailable)?;
+        }
+        Ok(())
     }
 
-    fn set_z_poly(&mut self) {
-        self.prover_polys.z = self.get_blinded_z_poly(
-            self.pub_coin.b9,
-            self.pub_coin.b8,
-            self.pub_coin.b7,
-            self.pub_coin.beta,
-            self.pub_coin.gamma,
-        );
+    fn new_transcript(&self) -> Transcript {
+        #[cfg(test)]
+        if self.fixed_challenges {
+            return Transcript::fixed(self.pub_coin.clone(), self.field);
+        }
+
+        self.vk.transcript(self.circuit.get_public_inputs())
     }
 
-    fn set_t_polys(&mut self) {
-        let t = self.get_t_poly();
-        let mut t_lo_coefficients = Vec::new();
-        let mut t_mid_coefficients = Vec::new();
-        let mut t_hi_coefficients = Vec::new();
+    fn set_wire_polys(&mut self) {
+        let [b1, b2, b3, b4, b5, b6, ..] = self.blinding;
 
-        let num_coefficients = (t.degree + 1) / 3;
-        for i in 0..num_coefficients {
-            t_lo_coefficients.push(t.coefficients[i as usize]);
-        }
+        self.prover_polys.a = self.get_blinded_wire_poly(&self.circuit.circuit_polys.left, b2, b1);
 
-        for i in 0..num_coefficients {
-            t_mid_coefficients.push(t.coefficients[(num_coefficients + i) as usize]);
-        }
+        self.prover_polys.b = self.get_blinded_wire_poly(&self.circuit.circuit_polys.right, b4, b3);
 
-        for i in 0..num_coefficients {
-            t_hi_coefficients.push(t.coefficients[((num_coefficients * 2) + i) as usize]);
-        }
+        self.prover_polys.c =
+            self.get_blinded_wire_poly(&self.circuit.circuit_polys.output, b6, b5);
+    }
 
-        self.prover_polys.t = t.copy();
-        self.prover_polys.t_lo = Polynomial {
-            degree: num_coefficients - 1,
-            coefficients: t_lo_coefficients,
-            field: t.field.clone(),
-        };
+    fn set_z_poly(&mut self) -> Option<()> {
+        let [.., b7, b8, b9] = self.blinding;
 
-        self.prover_polys.t_mid = Polynomial {
-            degree: num_coefficients - 1,
-            coefficients: t_mid_coefficients,
-            field: t.field.clone(),
-        };
+        self.prover_polys.z =
+            self.get_blinded_z_poly(b9, b8, b7, self.pub_coin.beta, self.pub_coin.gamma)?;
+        Some(())
+    }
 
-        self.prover_polys.t_hi = Polynomial {
-            degree: num_coefficients - 1,
-            coefficients: t_hi_coefficients,
-            field: t.field,
+    // t = t_lo + X^(n+2) * t_mid + X^(2n+4) * t_hi, each part of degree < n + 2
+    fn set_t_polys(&mut self) -> Result<(), ProverError> {
+        let t = self.get_t_poly()?;
+        let part_len = self.circuit.domain.size + 2;
+        let part = |i: usize| {
+            let coefficients = t.coefficients();
+            let start = (i * part_len).min(coefficients.len());
+            let end = ((i + 1) * part_len).min(coefficients.len());
+            Polynomial::new(coefficients[start..end].to_vec(), self.field)
         };
+
+        self.prover_polys.t_lo = part(0);
+        self.prover_polys.t_mid = part(1);
+        self.prover_polys.t_hi = part(2);
+        self.prover_polys.t = t;
+        Ok(())
     }
 
     fn set_r_poly(&mut self) {
@@ -161,14 +296,8 @@ impl Prover {
         self.opening_evals.b = self.prover_polys.b.eval(self.pub_coin.zed);
         self.opening_evals.c = self.prover_polys.c.eval(self.pub_coin.zed);
 
-        self.opening_evals.left_copy = self
-            .py_circuit
-            .circuit
-            .circuit_polys
-            .left_copy
-            .eval(self.pub_coin.zed);
+        self.opening_evals.left_copy = self.circuit.circuit_polys.left_copy.eval(self.pub_coin.zed);
         self.opening_evals.right_copy = self
-            .py_circuit
             .circuit
             .circuit_polys
             .right_copy
@@ -176,10 +305,10 @@ impl Prover {
 
         self.opening_evals.t = self.prover_polys.t.eval(self.pub_coin.zed);
 
-        self.opening_evals.z = self.prover_polys.z.eval(
-            self.field
-                .multiply(self.pub_coin.zed, self.py_circuit.circuit.roots[1]),
-        );
+        self.opening_evals.z = self
+            .prover_polys
+            .z
+            .eval(self.pub_coin.zed * self.circuit.domain.generator);
     }
 
     fn set_r_opening_eval(&mut self) {
@@ -189,478 +318,238 @@ impl Prover {
     fn commit_poly(&self, poly: &Polynomial) -> CurvePoint {
         let mut res = CurvePoint::point_at_infinity();
 
-        for i in 0..poly.coefficients.len() {
-            res = self.ecc.add(
+        for (i, coefficient) in poly.coefficients().iter().enumerate() {
+            res = self.srs.ecc.add(
                 &res,
                 &self
+                    .srs
                     .ecc
-                    .multiply(poly.coefficients[i], &self.srs.g_1_points[i]),
+                    .multiply(coefficient.value, &self.srs.g_1_points[i]),
             );
         }
 
         res
     }
 
-    fn get_blinded_wire_poly(&self, wire_poly: &Polynomial, rand1: u32, rand2: u32) -> Polynomial {
-        let blinded_z_poly = self.get_blinded_z_h_poly(1, vec![rand1, rand2]);
-
-        Polynomial::poly_add(&blinded_z_poly, wire_poly)
+    fn get_blinded_wire_poly(
+        &self,
+        wire_poly: &Polynomial,
+        rand1: FieldElement,
+        rand2: FieldElement,
+    ) -> Polynomial {
+        self.get_blinded_z_h_poly(vec![rand1, rand2]) + wire_poly
     }
 
     fn get_blinded_z_poly(
         &mut self,
-        rand1: u32,
-        rand2: u32,
-        rand3: u32,
-        beta: u32,
-        gamma: u32,
-    ) -> Polynomial {
-        let blinded_z_poly = self.get_blinded_z_h_poly(2, vec![rand1, rand2, rand3]);
-        self.py_circuit.build_acc(beta, gamma);
-        let acc_poly = &self.py_circuit.circuit.circuit_polys.acc;
-
-        Polynomial::poly_add(&blinded_z_poly, acc_poly)
+        rand1: FieldElement,
+        rand2: FieldElement,
+        rand3: FieldElement,
+        beta: FieldElement,
+        gamma: FieldElement,
+    ) -> Option<Polynomial> {
+        let blinded_z_poly = self.get_blinded_z_h_poly(vec![rand1, rand2, rand3]);
+        self.circuit.build_accumulator(beta, gamma)?;
+
+        Some(blinded_z_poly + &self.circuit.circuit_polys.acc)
     }
 
-    fn get_blinded_z_h_poly(&self, degree: u32, rands: Vec<u32>) -> Polynomial {
-        let blinding_poly = Polynomial {
-            degree,
-            coefficients: rands,
-            field: self.field.clone(),
-        };
-
-        Polynomial::poly_multiply(&self.py_circuit.circuit.circuit_polys.z_h, &blinding_poly)
+    // Z_H(X) * (rands[0] + rands[1] * X + ...)
+    fn get_blinded_z_h_poly(&self, rands: Vec<FieldElement>) -> Polynomial {
+        &self.circuit.circuit_polys.z_h * Polynomial::new(rands, self.field)
     }
 
-    fn get_t_poly(&self) -> Polynomial {
-        let mut first_term = Polynomial::poly_multiply(&self.prover_polys.a, &self.prover_polys.b);
-        first_term = Polynomial::poly_multiply(
-            &first_term,
-            &self.py_circuit.circuit.circuit_polys.multiply_selector,
-        );
-        first_term = Polynomial::poly_add(
-            &first_term,
-            &Polynomial::poly_multiply(
-                &self.prover_polys.a,
-                &self.py_circuit.circuit.circuit_polys.left_selector,
-            ),
-        );
-
-        first_term = Polynomial::poly_add(
-            &first_term,
-            &Polynomial::poly_multiply(
-                &self.prover_polys.b,
-                &self.py_circuit.circuit.circuit_polys.right_selector,
-            ),
-        );
-
-        first_term = Polynomial::poly_add(
-            &first_term,
-            &Polynomial::poly_multiply(
-                &self.prover_polys.c,
-                &self.py_circuit.circuit.circuit_polys.output_selector,
-            ),
-        );
-
-        let helper1 = Polynomial {
-            degree: 1,
-            coefficients: vec![self.pub_coin.gamma, self.pub_coin.beta],
-            field: self.field.clone(),
-        };
-        let helper2 = Polynomial {
-            degree: 1,
-            coefficients: vec![
-                self.pub_coin.gamma,
-                self.field.multiply(2, self.pub_coin.beta),
-            ],
-            field: self.field.clone(),
-        };
-        let helper3 = Polynomial {
-            degree: 1,
-            coefficients: vec![
-                self.pub_coin.gamma,
-                self.field.multiply(3, self.pub_coin.beta),
-            ],
-            field: self.field.clone(),
-        };
-        let mut second_term = Polynomial::poly_add(&self.prover_polys.a, &helper1);
-        second_term = Polynomial::poly_multiply(
-            &second_term,
-            &Polynomial::poly_add(&self.prover_polys.b, &helper2),
-        );
-        second_term = Polynomial::poly_multiply(
-            &second_term,
-            &Polynomial::poly_add(&self.prover_polys.c, &helper3),
-        );
-        second_term = Polynomial::poly_multiply(&second_term, &self.prover_polys.z);
-        second_term = Polynomial::scalar_multiply(&second_term, self.pub_coin.alpha);
-
-        let gamma_poly = Polynomial {
-            degree: 0,
-            coefficients: vec![self.pub_coin.gamma],
-            field: self.field.clone(),
-        };
-        let mut third_term = Polynomial::poly_add(
-            &Polynomial::poly_add(
-                &self.prover_polys.a,
-                &self
-                    .py_circuit
-                    .circuit
-                    .circuit_polys
-                    .left_copy
-                    .scalar_multiply(self.pub_coin.beta),
-            ),
-            &gamma_poly,
-        );
+    // Computes the quotient pointwise on a coset of a larger domain when the field has
+    // one, like production PLONK. F_17 has no 2-adic subgroup that big, so the toy
+    // setup always takes the coefficient-form fallback.
+    fn get_t_poly(&self) -> Result<Polynomial, ProverError> {
+        let n = self.circuit.domain.size;
+        match self.get_t_poly_on_coset() {
+            // if Z_H does not divide t_z, the interpolant of t_z / Z_H cannot have
+            // degree deg(t_z) - n, or t * Z_H would equal t_z on the whole coset
+            Some(t) if t.degree() + n > self.t_z_degree() => {
+                Err(ProverError::NonzeroQuotientRemainder)
+            }
+            Some(t) => Ok(t),
+            None => self.get_t_poly_by_division(),
+        }
+    }
 
-        third_term = Polynomial::poly_multiply(
-            &third_term,
-            &Polynomial::poly_add(
-                &self.prover_polys.b,
-                &Polynomial::poly_add(
-                    &self
-                        .py_circuit
-                        .circuit
-                        .circuit_polys
-                        .right_copy
-                        .scalar_multiply(self.pub_coin.beta),
-                    &gamma_poly,
-                ),
-            ),
-        );
+    // the permutation term dominates: deg(a) + deg(b) + deg(c) + deg(z)
+    fn t_z_degree(&self) -> usize {
+        let polys = &self.prover_polys;
+        polys.a.degree() + polys.b.degree() + polys.c.degree() + polys.z.degree()
+    }
 
-        third_term = Polynomial::poly_multiply(
-            &third_term,
-            &Polynomial::poly_add(
-                &self.prover_polys.c,
-                &Polynomial::poly_add(
-                    &self
-                        .py_circuit
-                        .circuit
-                        .circuit_polys
-                        .output_copy
-                        .scalar_multiply(self.pub_coin.beta),
-                    &gamma_poly,
-                ),
-            ),
+    fn get_t_poly_on_coset(&self) -> Option<Polynomial> {
+        let circuit = &self.circuit;
+        let polys = &self.prover_polys;
+        let coin = &self.pub_coin;
+
+        let coset = self.coset.as_ref()?;
+        let extended = &coset.domain;
+        let shift = coset.shift;
+        let fixed =
+            |values: &[FieldElement]| Evaluations::from_values(values.to_vec(), extended, shift);
+
+        let on_coset =
+            |poly: &Polynomial| Evaluations::from_polynomial_on_coset(poly, extended, shift);
+        let a = on_coset(&polys.a);
+        let b = on_coset(&polys.b);
+        let c = on_coset(&polys.c);
+        let z = on_coset(&polys.z);
+        // z(ωX) on the points s * ω_ext^i is z on the coset shifted by s * ω
+        let z_omega = Evaluations::from_values(
+            Evaluations::from_polynomial_on_coset(
+                &polys.z,
+                extended,
+                shift * circuit.domain.generator,
+            )
+            .values,
+            extended,
+            shift,
         );
 
-        let mut z_at_w_x = Polynomial {
-            degree: self.prover_polys.z.degree,
-            coefficients: vec![0; self.prover_polys.z.coefficients.len()],
-            field: self.prover_polys.z.field.clone(),
-        };
+        let gate = &a * &b * fixed(&coset.multiply_selector)
+            + &a * fixed(&coset.left_selector)
+            + &b * fixed(&coset.right_selector)
+            + &c * fixed(&coset.output_selector)
+            + fixed(&coset.constant_selector)
+            + on_coset(&circuit.circuit_polys.public_input);
+
+        let beta_x = fixed(&coset.x) * coin.beta;
+        let identity_permutation = (&a + &beta_x + coin.gamma)
+            * (&b + &beta_x * circuit.k1 + coin.gamma)
+            * (&c + &beta_x * circuit.k2 + coin.gamma)
+            * &z;
+        let sigma_permutation = (&a + fixed(&coset.left_copy) * coin.beta + coin.gamma)
+            * (&b + fixed(&coset.right_copy) * coin.beta + coin.gamma)
+            * (&c + fixed(&coset.output_copy) * coin.beta + coin.gamma)
+            * &z_omega;
+        let first_row = (&z - self.field.one()) * fixed(&coset.lagrange_1) * coin.alpha.pow(2);
+
+        let t_z = gate + (identity_permutation - sigma_permutation) * coin.alpha + first_row;
+        Some((t_z * fixed(&coset.z_h_inverses)).interpolate())
+    }
 
-        for i in 0..z_at_w_x.coefficients.len() {
-            z_at_w_x.coefficients[i] = self.field.multiply(
-                self.field
-                    .exponent(self.py_circuit.circuit.roots[1], i as u32),
-                self.prover_polys.z.coefficients[i],
-            );
+    fn get_t_poly_by_division(&self) -> Result<Polynomial, ProverError> {
+        let circuit = &self.circuit;
+        let circuit_polys = &circuit.circuit_polys;
+        let polys = &self.prover_polys;
+        let coin = &self.pub_coin;
+
+        let gate = &polys.a * &polys.b * &circuit_polys.multiply_selector
+            + &polys.a * &circuit_polys.left_selector
+            + &polys.b * &circuit_polys.right_selector
+            + &polys.c * &circuit_polys.output_selector
+            + &circuit_polys.constant_selector
+            + &circuit_polys.public_input;
+
+        let beta_x = Polynomial::new(vec![self.field.zero(), coin.beta], self.field);
+        let identity_permutation = (&polys.a + &beta_x + coin.gamma)
+            * (&polys.b + &beta_x * circuit.k1 + coin.gamma)
+            * (&polys.c + &beta_x * circuit.k2 + coin.gamma)
+            * &polys.z;
+        let sigma_permutation = (&polys.a + &circuit_polys.left_copy * coin.beta + coin.gamma)
+            * (&polys.b + &circuit_polys.right_copy * coin.beta + coin.gamma)
+            * (&polys.c + &circuit_polys.output_copy * coin.beta + coin.gamma)
+            * polys.z.scale_input(circuit.domain.generator);
+
+        let lagrange_1 = Polynomial::get_lagrange_1_poly(&self.field, &circuit.domain.elements);
+        let first_row = (&polys.z - self.field.one()) * lagrange_1 * coin.alpha.pow(2);
+
+        let t_z = gate + (identity_permutation - sigma_permutation) * coin.alpha + first_row;
+
+        let (quotient, remainder) = t_z.divide_by_vanishing(circuit.domain.size);
+        if !remainder.is_zero() {
+            return Err(ProverError::NonzeroQuotientRemainder);
         }
-        third_term = Polynomial::poly_multiply(&third_term, &z_at_w_x);
-        third_term = third_term.scalar_multiply(self.pub_coin.alpha);
-        third_term = Polynomial::additive_inverse_poly(&third_term);
-
-        let inverse_one_poly = Polynomial::additive_inverse_poly(&Polynomial {
-            degree: 0,
-            coefficients: vec![1],
-            field: self.field.clone(),
-        });
-        let mut fourth_term = Polynomial::poly_add(&self.prover_polys.z, &inverse_one_poly);
-        fourth_term = Polynomial::poly_multiply(
-            &fourth_term,
-            &Polynomial::get_lagrange_1_poly(
-                &self.field,
-                (self.py_circuit.circuit.roots.len() - 1) as u32,
-                &self.py_circuit.circuit.roots,
-            ),
-        );
-        fourth_term = fourth_term.scalar_multiply(self.field.exponent(self.pub_coin.alpha, 2));
-
-        let t_z = Polynomial::poly_add(
-            &first_term,
-            &Polynomial::poly_add(
-                &second_term,
-                &Polynomial::poly_add(&third_term, &fourth_term),
-            ),
-        );
-
-        let (quotient, _remainder) =
-            Polynomial::poly_divide(&t_z, &self.py_circuit.circuit.circuit_polys.z_h);
-
-        quotient
+        Ok(quotient)
     }
 
     fn get_r_poly(&self) -> Polynomial {
-        let first_term = Polynomial::poly_add(
-            &Polynomial::scalar_multiply(
-                &self.py_circuit.circuit.circuit_polys.multiply_selector,
-                self.field
-                    .multiply(self.opening_evals.a, self.opening_evals.b),
-            ),
-            &Polynomial::poly_add(
-                &Polynomial::scalar_multiply(
-                    &self.py_circuit.circuit.circuit_polys.left_selector,
-                    self.opening_evals.a,
-                ),
-                &Polynomial::poly_add(
-                    &Polynomial::scalar_multiply(
-                        &self.py_circuit.circuit.circuit_polys.right_selector,
-                        self.opening_evals.b,
-                    ),
-                    &Polynomial::scalar_multiply(
-                        &self.py_circuit.circuit.circuit_polys.output_selector,
-                        self.opening_evals.c,
-                    ),
-                ),
-            ),
-        );
-
-        let second_term_scalar = self.field.multiply(
-            self.field.multiply(
-                self.field.add(
-                    self.opening_evals.a,
-                    self.field.add(
-                        self.field.multiply(self.pub_coin.beta, self.pub_coin.zed),
-                        self.pub_coin.gamma,
-                    ),
-                ),
-                self.field.multiply(
-                    self.field.add(
-                        self.opening_evals.b,
-                        self.field.add(
-                            self.field.multiply(
-                                self.field.multiply(self.pub_coin.beta, 2),
-                                self.pub_coin.zed,
-                            ),
-                            self.pub_coin.gamma,
-                        ),
-                    ),
-                    self.field.add(
-                        self.opening_evals.c,
-                        self.field.add(
-                            self.field.multiply(
-                                self.field.multiply(self.pub_coin.beta, 3),
-                                self.pub_coin.zed,
-                            ),
-                            self.pub_coin.gamma,
-                        ),
-                    ),
-                ),
-            ),
-            self.pub_coin.alpha,
-        );
-
-        let second_term = Polynomial::scalar_multiply(&self.prover_polys.z, second_term_scalar);
-
-        let third_term_scalar = self.field.multiply(
-            self.field.multiply(
-                self.field.add(
-                    self.opening_evals.a,
-                    self.field.add(
-                        self.field
-                            .multiply(self.pub_coin.beta, self.opening_evals.left_copy),
-                        self.pub_coin.gamma,
-                    ),
-                ),
-                self.field.multiply(
-                    self.field.add(
-                        self.opening_evals.b,
-                        self.field.add(
-                            self.field
-                                .multiply(self.pub_coin.beta, self.opening_evals.right_copy),
-                            self.pub_coin.gamma,
-                        ),
-                    ),
-                    self.field
-                        .multiply(self.pub_coin.beta, self.opening_evals.z),
-                ),
-            ),
-            self.pub_coin.alpha,
-        );
-        let third_term = Polynomial::additive_inverse_poly(&Polynomial::scalar_multiply(
-            &self.py_circuit.circuit.circuit_polys.output_copy,
-            third_term_scalar,
-        ));
-
-        let fourth_term_scalar = self.field.multiply(
-            Polynomial::get_lagrange_1_poly(
-                &self.field,
-                (self.py_circuit.circuit.roots.len() - 1) as u32,
-                &self.py_circuit.circuit.roots,
-            )
-            .eval(self.pub_coin.zed),
-            self.field.exponent(self.pub_coin.alpha, 2),
-        );
-        let fourth_term = Polynomial::scalar_multiply(&self.prover_polys.z, fourth_term_scalar);
-
-        Polynomial::poly_add(
-            &first_term,
-            &Polynomial::poly_add(
-                &second_term,
-                &Polynomial::poly_add(&third_term, &fourth_term),
-            ),
-        )
+        let evals = &self.opening_evals;
+        let coin = &self.pub_coin;
+        let circuit = &self.circuit;
+        let circuit_polys = &circuit.circuit_polys;
+
+        let gate = &circuit_polys.multiply_selector * (evals.a * evals.b)
+            + &circuit_polys.left_selector * evals.a
+            + &circuit_polys.right_selector * evals.b
+            + &circuit_polys.output_selector * evals.c
+            + &circuit_polys.constant_selector;
+
+        let identity_permutation_scalar = (evals.a + coin.beta * coin.zed + coin.gamma)
+            * (evals.b + coin.beta * circuit.k1 * coin.zed + coin.gamma)
+            * (evals.c + coin.beta * circuit.k2 * coin.zed + coin.gamma)
+            * coin.alpha;
+
+        let sigma_permutation_scalar = (evals.a + coin.beta * evals.left_copy + coin.gamma)
+            * (evals.b + coin.beta * evals.right_copy + coin.gamma)
+            * coin.beta
+            * evals.z
+            * coin.alpha;
+
+        let first_row_scalar =
+            Polynomial::get_lagrange_1_poly(&self.field, &circuit.domain.elements).eval(coin.zed)
+                * coin.alpha.pow(2);
+
+        gate + &self.prover_polys.z * (identity_permutation_scalar + first_row_scalar)
+            - &circuit_polys.output_copy * sigma_permutation_scalar
     }
 
     fn get_w_poly(&self) -> Polynomial {
-        let mut first_term = Polynomial::poly_add(
-            &self.prover_polys.t_lo,
-            &Polynomial::poly_add(
-                &Polynomial::scalar_multiply(
-                    &self.prover_polys.t_mid,
-                    self.field.exponent(
-                        self.pub_coin.zed,
-                        (self.py_circuit.circuit.roots.len() + 2) as u32,
-                    ),
-                ),
-                &Polynomial::scalar_multiply(
-                    &self.prover_polys.t_hi,
-                    self.field.exponent(
-                        self.pub_coin.zed,
-                        ((2 * self.py_circuit.circuit.roots.len()) + 4) as u32,
-                    ),
-                ),
-            ),
-        );
-
-        first_term = Polynomial::poly_add(
-            &first_term,
-            &Polynomial {
-                degree: 0,
-                coefficients: vec![self.field.additive_inverse(self.opening_evals.t)],
-                field: self.field.clone(),
-            },
-        );
-
-        let mut second_term = Polynomial::scalar_multiply(
-            &Polynomial::poly_add(
-                &self.prover_polys.r,
-                &Polynomial::from_scalar(
-                    self.field.additive_inverse(self.opening_evals.r),
-                    self.field.clone(),
-                ),
-            ),
-            self.pub_coin.v,
-        );
-
-        second_term = Polynomial::poly_add(
-            &second_term,
-            &Polynomial::scalar_multiply(
-                &Polynomial::poly_add(
-                    &self.prover_polys.a,
-                    &Polynomial::from_scalar(
-                        self.field.additive_inverse(self.opening_evals.a),
-                        self.field.clone(),
-                    ),
-                ),
-                self.field.exponent(self.pub_coin.v, 2),
-            ),
-        );
-
-        second_term = Polynomial::poly_add(
-            &second_term,
-            &Polynomial::scalar_multiply(
-                &Polynomial::poly_add(
-                    &self.prover_polys.b,
-                    &Polynomial::from_scalar(
-                        self.field.additive_inverse(self.opening_evals.b),
-                        self.field.clone(),
-                    ),
-                ),
-                self.field.exponent(self.pub_coin.v, 3),
-            ),
-        );
-
-        second_term = Polynomial::poly_add(
-            &second_term,
-            &Polynomial::scalar_multiply(
-                &Polynomial::poly_add(
-                    &self.prover_polys.c,
-                    &Polynomial::from_scalar(
-                        self.field.additive_inverse(self.opening_evals.c),
-                        self.field.clone(),
-                    ),
-                ),
-                self.field.exponent(self.pub_coin.v, 4),
-            ),
-        );
-
-        second_term = Polynomial::poly_add(
-            &second_term,
-            &Polynomial::scalar_multiply(
-                &Polynomial::poly_add(
-                    &self.py_circuit.circuit.circuit_polys.left_copy,
-                    &Polynomial::from_scalar(
-                        self.field.additive_inverse(self.opening_evals.left_copy),
-                        self.field.clone(),
-                    ),
-                ),
-                self.field.exponent(self.pub_coin.v, 5),
-            ),
-        );
-
-        second_term = Polynomial::poly_add(
-            &second_term,
-            &Polynomial::scalar_multiply(
-                &Polynomial::poly_add(
-                    &self.py_circuit.circuit.circuit_polys.right_copy,
-                    &Polynomial::from_scalar(
-                        self.field.additive_inverse(self.opening_evals.right_copy),
-                        self.field.clone(),
-                    ),
-                ),
-                self.field.exponent(self.pub_coin.v, 6),
-            ),
-        );
-
-        let denominator = Polynomial {
-            degree: 1,
-            coefficients: vec![self.field.additive_inverse(self.pub_coin.zed), 1],
-            field: self.field.clone(),
-        };
+        let zed = self.pub_coin.zed;
+        let v = self.pub_coin.v;
+        let n = self.circuit.domain.size as u64;
+        let polys = &self.prover_polys;
+        let evals = &self.opening_evals;
+        let circuit_polys = &self.circuit.circuit_polys;
+
+        let t_at_zed =
+            &polys.t_lo + &polys.t_mid * zed.pow(n + 2) + &polys.t_hi * zed.pow((2 * n) + 4)
+                - evals.t;
+
+        let openings = [
+            (&polys.r, evals.r),
+            (&polys.a, evals.a),
+            (&polys.b, evals.b),
+            (&polys.c, evals.c),
+            (&circuit_polys.left_copy, evals.left_copy),
+            (&circuit_polys.right_copy, evals.right_copy),
+        ];
+
+        // v^1 * (r - r_bar) + v^2 * (a - a_bar) + ... + v^6 * (right_copy - right_copy_bar)
+        let mut numerator = t_at_zed;
+        for (i, (poly, opening)) in openings.iter().enumerate() {
+            numerator = numerator + (*poly - *opening) * v.pow(i as u64 + 1);
+        }
 
-        let (quotient, _remainder) = Polynomial::poly_divide(
-            &Polynomial::poly_add(&first_term, &second_term),
-            &denominator,
-        );
+        let (quotient, _remainder) = numerator.divide_by_linear(zed);
         quotient
     }
 
     fn get_wz_poly(&self) -> Polynomial {
-        let numerator = Polynomial::poly_add(
-            &self.prover_polys.z,
-            &Polynomial::from_scalar(
-                self.field.additive_inverse(self.opening_evals.z),
-                self.field.clone(),
-            ),
-        );
-        let denominator = Polynomial {
-            degree: 1,
-            coefficients: vec![
-                self.field.additive_inverse(
-                    self.field
-                        .multiply(self.pub_coin.zed, self.py_circuit.circuit.roots[1]),
-                ),
-                1,
-            ],
-            field: self.field.clone(),
-        };
+        let zed_omega = self.pub_coin.zed * self.circuit.domain.generator;
 
-        let (quotient, _remainder) = Polynomial::poly_divide(&numerator, &denominator);
+        let (quotient, _remainder) =
+            (&self.prover_polys.z - self.opening_evals.z).divide_by_linear(zed_omega);
         quotient
     }
 }
 
+#[cfg(test)]
 fn test_setup_prover() -> Prover {
-    let mut srs = constants::SRS_BY_HAND;
-    srs.generate_g_1_points();
-
-    let mut prover = Prover::new(constants::FIELD_17.clone(), vec![3, 4, 5], srs);
+    let mut prover = Prover::new(
+        PythagoreanCircuit::new(constants::FIELD_17),
+        vec![3, 4, 5],
+        test_utils::srs_by_hand(),
+    )
+    .unwrap();
     prover.set_public_coin(constants::PUB_COIN.clone());
+    prover.set_blinding_rng(Box::new(FixedBlinding::new(&constants::BLINDING_BY_HAND)));
 
-    prover.generate_proof();
+    prover.generate_proof().unwrap();
 
     prover
 }
@@ -669,20 +558,26 @@ fn test_setup_prover() -> Prover {
 fn test_blinded_wire_polys() {
     let prover = test_setup_prover();
 
-    let a_coefficients = prover
-        .get_blinded_wire_poly(&prover.py_circuit.circuit.circuit_polys.left, 4, 7)
-        .coefficients;
-    assert_eq!(a_coefficients, vec![14, 6, 3, 3, 4, 7]);
+    let a_poly = prover.get_blinded_wire_poly(
+        &prover.circuit.circuit_polys.left,
+        prover.field.element(4),
+        prover.field.element(7),
+    );
+    assert_eq!(a_poly.coefficients(), vec![14, 6, 3, 3, 4, 7]);
 
-    let b_coefficients = prover
-        .get_blinded_wire_poly(&prover.py_circuit.circuit.circuit_polys.right, 12, 11)
-        .coefficients;
-    assert_eq!(b_coefficients, vec![12, 9, 14, 13, 12, 11]);
+    let b_poly = prover.get_blinded_wire_poly(
+        &prover.circuit.circuit_polys.right,
+        prover.field.element(12),
+        prover.field.element(11),
+    );
+    assert_eq!(b_poly.coefficients(), vec![12, 9, 14, 13, 12, 11]);
 
-    let c_coefficients = prover
-        .get_blinded_wire_poly(&prover.py_circuit.circuit.circuit_polys.output, 2, 16)
-        .coefficients;
-    assert_eq!(c_coefficients, vec![4, 6, 11, 4, 2, 16]);
+    let c_poly = prover.get_blinded_wire_poly(
+        &prover.circuit.circuit_polys.output,
+        prover.field.element(2),
+        prover.field.element(16),
+    );
+    assert_eq!(c_poly.coefficients(), vec![4, 6, 11, 4, 2, 16]);
 
     let a_commitment = prover.commit_poly(&prover.prover_polys.a);
     assert!(CurvePoint::equals(
@@ -717,22 +612,22 @@ fn test_accumulator() {
 fn test_t_poly() {
     let prover = test_setup_prover();
 
-    let t_poly = prover.get_t_poly();
+    let t_poly = prover.get_t_poly().unwrap();
     assert_eq!(
-        t_poly.coefficients,
+        t_poly.coefficients(),
         vec![11, 16, 13, 9, 0, 13, 13, 8, 1, 2, 10, 1, 15, 6, 16, 2, 7, 11]
     );
 
     assert_eq!(
-        prover.prover_polys.t_lo.coefficients,
+        prover.prover_polys.t_lo.coefficients(),
         vec![11, 16, 13, 9, 0, 13]
     );
     assert_eq!(
-        prover.prover_polys.t_mid.coefficients,
+        prover.prover_polys.t_mid.coefficients(),
         vec![13, 8, 1, 2, 10, 1]
     );
     assert_eq!(
-        prover.prover_polys.t_hi.coefficients,
+        prover.prover_polys.t_hi.coefficients(),
         vec![15, 6, 16, 2, 7, 11]
     );
 
@@ -745,6 +640,47 @@ fn test_t_poly() {
     assert!(CurvePoint::equals(&t_hi_com, &CurvePoint { x: 91, y: 66 }));
 }
 
+#[test]
+fn test_t_poly_on_coset_matches_division() {
+    // F_17 has no coset big enough, so check the pointwise path over Goldilocks
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let mut prover = Prover::new(
+        PythagoreanCircuit::new(field),
+        vec![3, 4, 5],
+        test_utils::srs_by_hand(),
+    )
+    .unwrap();
+
+    prover.set_public_coin(PublicCoin {
+        alpha: field.element(15),
+        beta: field.element(12),
+        gamma: field.element(13),
+        zed: field.element(5),
+        v: field.element(12),
+        u: field.element(4),
+    });
+    prover.blinding = field
+        .elements(&[7, 4, 11, 12, 16, 2, 14, 11, 7])
+        .try_into()
+        .unwrap();
+    prover.set_wire_polys();
+    prover.set_z_poly().unwrap();
+
+    let on_coset = prover.get_t_poly_on_coset().unwrap();
+    let by_division = prover.get_t_poly_by_division().unwrap();
+    assert_eq!(on_coset.degree(), 17);
+    assert_eq!(on_coset.coefficients(), by_division.coefficients());
+    assert!(test_setup_prover().get_t_poly_on_coset().is_none());
+
+    // and that it notices when Z_H does not divide t_z
+    prover.circuit.circuit_polys.output = Polynomial::new(vec![field.one()], field);
+    prover.set_wire_polys();
+    assert_eq!(
+        prover.get_t_poly().err(),
+        Some(ProverError::NonzeroQuotientRemainder)
+    );
+}
+
 #[test]
 fn test_opening_evals() {
     let prover = test_setup_prover();
@@ -770,7 +706,7 @@ fn test_opening_evals() {
 fn test_r_poly() {
     let prover = test_setup_prover();
 
-    let actual_coefficients = prover.prover_polys.r.coefficients;
+    let actual_coefficients = prover.prover_polys.r.coefficients();
     let expected_coefficients = vec![0, 16, 9, 13, 8, 15, 16];
     assert_eq!(actual_coefficients, expected_coefficients);
 }
@@ -779,11 +715,11 @@ fn test_r_poly() {
 fn test_w_polys() {
     let prover = test_setup_prover();
 
-    let actual_coefficients = prover.prover_polys.w.coefficients.clone();
+    let actual_coefficients = prover.prover_polys.w.coefficients();
     let expected_coefficients = vec![16, 13, 2, 9, 3, 5];
     assert_eq!(actual_coefficients, expected_coefficients);
 
-    let actual_coefficients = prover.prover_polys.wz.coefficients;
+    let actual_coefficients = prover.prover_polys.wz.coefficients();
     let expected_coefficients = vec![13, 14, 2, 13, 2, 14];
     assert_eq!(actual_coefficients, expected_coefficients);
 }
@@ -798,3 +734,91 @@ fn test_w_commits() {
     assert!(CurvePoint::equals(&w_com, &CurvePoint { x: 91, y: 35 }));
     assert!(CurvePoint::equals(&wz_com, &CurvePoint { x: 65, y: 98 }));
 }
+
+#[test]
+fn test_prover_errors() {
+    use crate::plonk_by_hand::circuit::ConstraintError;
+
+    let srs = test_utils::srs_by_hand();
+    let field = constants::FIELD_17;
+
+    let mut prover =
+        Prover::new(PythagoreanCircuit::new(field), vec![3, 4, 6], srs.copy()).unwrap();
+    assert_eq!(
+        prover.generate_proof().err(),
+        Some(ProverError::UnsatisfiedConstraints(ConstraintError::Gate {
+            gate: 2,
+            wire: 5,
+            expected: field.element(2),
+            actual: field.element(8),
+        }))
+    );
+
+    for inputs in [vec![3, 4], vec![3, 4, 5, 6]] {
+        let actual = inputs.len();
+        assert_eq!(
+            Prover::new(PythagoreanCircuit::new(field), inputs, srs.copy()).err(),
+            Some(ProverError::WrongInputCount {
+                expected: 3,
+                actual
+            })
+        );
+    }
+
+    let mut small_srs = srs.copy();
+    small_srs.g_1_points.truncate(6);
+    assert_eq!(
+        Prover::new(
+            PythagoreanCircuit::new(field),
+            vec![3, 4, 5],
+            small_srs.copy()
+        )
+        .err(),
+        Some(ProverError::SrsTooSmall {
+            degree: 6,
+            points: 6
+        })
+    );
+
+    // a key built on a full SRS still checks the SRS it proves with
+    let (pk, _) = setup(PythagoreanCircuit::new(field), &srs).unwrap();
+    let mut prover = Prover::from_key(&pk, vec![3, 4, 5], small_srs).unwrap();
+    assert_eq!(
+        prover.generate_proof().err(),
+        Some(ProverError::SrsTooSmall {
+            degree: 6,
+            points: 6
+        })
+    );
+
+    // an rng that runs dry is an error, not a panic
+    struct NoEntropy;
+    impl BlindingRng for NoEntropy {
+        fn next_u64(&mut self) -> Option<u64> {
+            None
+        }
+    }
+    let mut prover =
+        Prover::new(PythagoreanCircuit::new(field), vec![3, 4, 5], srs.copy()).unwrap();
+    prover.set_blinding_rng(Box::new(NoEntropy));
+    assert_eq!(
+        prover.generate_proof().err(),
+        Some(ProverError::EntropyUnavailable)
+    );
+
+    // a wire polynomial that no longer matches the witness leaves a remainder
+    let mut prover = test_setup_prover();
+    prover.circuit.circuit_polys.output = Polynomial::new(vec![field.one()], field);
+    prover.set_wire_polys();
+    prover.set_z_poly().unwrap();
+    assert_eq!(
+        prover.get_t_poly_by_division().err(),
+        Some(ProverError::NonzeroQuotientRemainder)
+    );
+}
+
+#[test]
+#[should_panic(expected = "every fixed challenge must be a non-zero element")]
+fn test_fixed_public_coin_must_be_set() {
+    test_setup_prover().set_public_coin(PublicCoin::default());
+}
@@ -1,18 +1,13 @@
+use crate::math::field::FieldElement;
+
+// The verifier's challenges. The prover's blinding scalars are private and come from
+// a BlindingRng instead.
 #[derive(Default, Clone)]
 pub struct PublicCoin {
-    pub b1: u32,
-    pub b2: u32,
-    pub b3: u32,
-    pub b4: u32,
-    pub b5: u32,
-    pub b6: u32,
-    pub b7: u32,
-    pub b8: u32,
-    pub b9: u32,
-    pub alpha: u32,
-    pub beta: u32,
-    pub gamma: u32,
-    pub zed: u32,
-    pub v: u32,
-    pub u: u32,
+    pub alpha: FieldElement,
+    pub beta: FieldElement,
+    pub gamma: FieldElement,
+    pub zed: FieldElement,
+    pub v: FieldElement,
+    pub u: FieldElement,
 }
@@ -1,6 +1,7 @@
 use crate::math::field;
-use crate::math::roots_of_unity;
+use crate::math::roots_of_unity::DomainError;
 use crate::plonk_by_hand::circuit;
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
 
 pub struct PythagoreanCircuit {
     pub circuit: circuit::Circuit,
@@ -8,36 +9,22 @@ pub struct PythagoreanCircuit {
 
 impl PythagoreanCircuit {
     pub fn new(field: field::Field) -> PythagoreanCircuit {
-        let roots = roots_of_unity::get_roots_of_unity(4, &field);
-
         PythagoreanCircuit {
-            circuit: circuit::Circuit {
-                gates: Vec::new(),
-                inputs: Vec::new(),
-                witness: vec![0; 6],
-                field: field.clone(),
-                coset1: roots_of_unity::get_coset(2, &roots, &field),
-                coset2: roots_of_unity::get_coset(3, &roots, &field),
-                roots,
-                circuit_polys: Default::default(),
-            },
+            circuit: circuit::Circuit::new(field),
         }
     }
 
-    pub fn build_circuit_with_inputs(&mut self, inputs: Vec<u32>) {
-        // inputs[0]: X_0, inputs[1]: X_2, inputs[2]: X_4
-        self.circuit
-            .set_inputs(vec![(inputs[0], 0), (inputs[1], 2), (inputs[2], 4)]);
-
-        self.build_circuit();
-
-        self.circuit.compute_witness();
+    #[cfg(test)]
+    pub fn build_circuit_with_inputs(&mut self, inputs: Vec<u64>) {
+        self.build_circuit().unwrap();
+        self.circuit.assign_witness(&inputs).unwrap();
 
         // private polynomials
         self.circuit.build_polynomials_with_input();
     }
 
-    pub fn build_circuit(&mut self) {
+    // The inputs are a, b and c, on X_0, X_2 and X_4
+    pub fn build_circuit(&mut self) -> Result<(), DomainError> {
         // X_0 * X_0 = X_1
         self.circuit.insert_gate(circuit::Gate {
             left_index: 0,
@@ -70,12 +57,26 @@ impl PythagoreanCircuit {
             gate_type: circuit::GateType::Addition,
         });
 
+        self.circuit.insert_input(0);
+        self.circuit.insert_input(2);
+        self.circuit.insert_input(4);
+
         // public polynomials
-        self.circuit.build_polynomials();
+        self.circuit.build_polynomials()
+    }
+}
+
+impl CircuitDefinition for PythagoreanCircuit {
+    fn circuit(&self) -> &circuit::Circuit {
+        &self.circuit
+    }
+
+    fn circuit_mut(&mut self) -> &mut circuit::Circuit {
+        &mut self.circuit
     }
 
-    pub fn build_acc(&mut self, beta: u32, gamma: u32) {
-        self.circuit.build_accumulator(beta, gamma);
+    fn build_gates(&mut self) -> Result<(), DomainError> {
+        self.build_circuit()
     }
 }
 
@@ -126,30 +127,30 @@ fn test_circuit() {
     );
 
     let left_poly = pythagorean_circuit.circuit.circuit_polys.left;
-    assert_eq!(vec![1, 13, 3, 3], left_poly.coefficients);
+    assert_eq!(vec![1, 13, 3, 3], left_poly.coefficients());
     let right_poly = pythagorean_circuit.circuit.circuit_polys.right;
-    assert_eq!(vec![7, 3, 14, 13], right_poly.coefficients);
+    assert_eq!(vec![7, 3, 14, 13], right_poly.coefficients());
     let output_poly = pythagorean_circuit.circuit.circuit_polys.output;
-    assert_eq!(vec![6, 5, 11, 4], output_poly.coefficients);
+    assert_eq!(vec![6, 5, 11, 4], output_poly.coefficients());
 
     let left_selector_poly = pythagorean_circuit.circuit.circuit_polys.left_selector;
-    assert_eq!(vec![13, 1, 4, 16], left_selector_poly.coefficients);
+    assert_eq!(vec![13, 1, 4, 16], left_selector_poly.coefficients());
     let right_selector_poly = pythagorean_circuit.circuit.circuit_polys.right_selector;
-    assert_eq!(vec![13, 1, 4, 16], right_selector_poly.coefficients);
+    assert_eq!(vec![13, 1, 4, 16], right_selector_poly.coefficients());
     let output_selector_poly = pythagorean_circuit.circuit.circuit_polys.output_selector;
-    assert_eq!(vec![16, 0, 0, 0], output_selector_poly.coefficients);
+    assert_eq!(vec![16], output_selector_poly.coefficients());
     let multiply_selector_poly = pythagorean_circuit.circuit.circuit_polys.multiply_selector;
-    assert_eq!(vec![5, 16, 13, 1], multiply_selector_poly.coefficients);
+    assert_eq!(vec![5, 16, 13, 1], multiply_selector_poly.coefficients());
 
     let left_copy_poly = pythagorean_circuit.circuit.circuit_polys.left_copy;
-    assert_eq!(vec![7, 13, 10, 6], left_copy_poly.coefficients);
+    assert_eq!(vec![7, 13, 10, 6], left_copy_poly.coefficients());
     let right_copy_poly = pythagorean_circuit.circuit.circuit_polys.right_copy;
-    assert_eq!(vec![4, 0, 13, 1], right_copy_poly.coefficients);
+    assert_eq!(vec![4, 0, 13, 1], right_copy_poly.coefficients());
     let output_copy_poly = pythagorean_circuit.circuit.circuit_polys.output_copy;
-    assert_eq!(vec![6, 7, 3, 14], output_copy_poly.coefficients);
+    assert_eq!(vec![6, 7, 3, 14], output_copy_poly.coefficients());
 
     assert_eq!(
         vec![16, 0, 0, 0, 1],
-        pythagorean_circuit.circuit.circuit_polys.z_h.coefficients
+        pythagorean_circuit.circuit.circuit_polys.z_h.coefficients()
     );
 }
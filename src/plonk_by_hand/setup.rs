@@ -0,0 +1,172 @@
+use crate::math::evaluations::Evaluations;
+use crate::math::field::FieldElement;
+use crate::math::roots_of_unity::{DomainError, EvaluationDomain};
+use crate::plonk_by_hand::circuit::Circuit;
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
+#[cfg(test)]
+use crate::plonk_by_hand::constants;
+use crate::plonk_by_hand::structured_reference_string::SRS;
+#[cfg(test)]
+use crate::plonk_by_hand::test_utils;
+use crate::plonk_by_hand::verifier::VerifyingKey;
+#[cfg(test)]
+use crate::PythagoreanCircuit;
+
+// Everything fixed by the circuit that the prover needs. Built once, then shared by
+// every proof of the same circuit.
+#[derive(Clone)]
+pub struct ProvingKey {
+    // gates, domain, and the selector and σ polynomials, with an empty witness
+    pub circuit: Circuit,
+    pub coset: Option<CosetEvaluations>,
+    // the transcript starts from the verifying key, so the prover needs it too
+    pub vk: VerifyingKey,
+}
+
+// The circuit's fixed polynomials on the coset where the prover computes the quotient
+// pointwise. Stored as values, the domain is borrowed when they are used.
+#[derive(Clone)]
+pub struct CosetEvaluations {
+    pub domain: EvaluationDomain,
+    pub shift: FieldElement,
+    pub x: Vec<FieldElement>,
+    pub z_h_inverses: Vec<FieldElement>,
+    pub lagrange_1: Vec<FieldElement>,
+    pub left_selector: Vec<FieldElement>,
+    pub right_selector: Vec<FieldElement>,
+    pub output_selector: Vec<FieldElement>,
+    pub multiply_selector: Vec<FieldElement>,
+    pub constant_selector: Vec<FieldElement>,
+    pub left_copy: Vec<FieldElement>,
+    pub right_copy: Vec<FieldElement>,
+    pub output_copy: Vec<FieldElement>,
+}
+
+// Lays out the circuit's gates and preprocesses them for both parties
+pub fn setup<C: CircuitDefinition>(
+    mut circuit: C,
+    srs: &SRS,
+) -> Result<(ProvingKey, VerifyingKey), DomainError> {
+    circuit.build_gates()?;
+    let pk = ProvingKey::new(circuit.circuit().clone(), srs);
+    let vk = pk.vk.clone();
+
+    Ok((pk, vk))
+}
+
+impl ProvingKey {
+    // The circuit must have its gates inserted and its polynomials built
+    pub fn new(circuit: Circuit, srs: &SRS) -> ProvingKey {
+        ProvingKey {
+            coset: CosetEvaluations::new(&circuit),
+            vk: VerifyingKey::new(&circuit, srs),
+            circuit,
+        }
+    }
+}
+
+impl CosetEvaluations {
+    // None when the field has no coset big enough, as for F_17
+    fn new(circuit: &Circuit) -> Option<CosetEvaluations> {
+        let field = circuit.field;
+        let n = circuit.domain.size;
+
+        // t_z has degree at most 3 * (n + 1) + (n + 2), from the blinded a, b, c and z
+        let domain = EvaluationDomain::new((4 * n + 6).next_power_of_two(), field).ok()?;
+        let (shift, _) = domain.coset_shifts().ok()?;
+
+        let x = domain.coset(shift);
+        let z_h_evals: Vec<FieldElement> = x
+            .iter()
+            .map(|x| circuit.domain.evaluate_vanishing_polynomial(*x))
+            .collect();
+        let z_h_inverses = field.batch_inverse(&z_h_evals)?;
+
+        // L_1(X) = (X^n - 1) / (n * (X - 1))
+        let x_minus_one: Vec<FieldElement> = x
+            .iter()
+            .map(|x| (*x - field.one()) * field.element(n as u64))
+            .collect();
+        let lagrange_1 = z_h_evals
+            .iter()
+            .zip(field.batch_inverse(&x_minus_one)?)
+            .map(|(z_h, inverse)| *z_h * inverse)
+            .collect();
+
+        let polys = &circuit.circuit_polys;
+        let on_coset = |poly| Evaluations::from_polynomial_on_coset(poly, &domain, shift).values;
+
+        Some(CosetEvaluations {
+            left_selector: on_coset(&polys.left_selector),
+            right_selector: on_coset(&polys.right_selector),
+            output_selector: on_coset(&polys.output_selector),
+            multiply_selector: on_coset(&polys.multiply_selector),
+            constant_selector: on_coset(&polys.constant_selector),
+            left_copy: on_coset(&polys.left_copy),
+            right_copy: on_coset(&polys.right_copy),
+            output_copy: on_coset(&polys.output_copy),
+            domain,
+            shift,
+            x,
+            z_h_inverses,
+            lagrange_1,
+        })
+    }
+}
+
+#[test]
+fn test_setup() {
+    use crate::field::Field;
+    use crate::math::ecc::CurvePoint;
+
+    let srs = test_utils::srs_by_hand();
+    let (pk, vk) = setup(PythagoreanCircuit::new(constants::FIELD_17), &srs).unwrap();
+    assert_eq!(pk.circuit.domain.size, 4);
+    assert!(pk.circuit.witness.iter().all(|value| value.is_zero()));
+    assert!(pk.coset.is_none());
+    assert_eq!(vk.domain.size, 4);
+    assert!(CurvePoint::equals(
+        &vk.commitments.output_copy,
+        &CurvePoint::new(18, 49)
+    ));
+
+    // the coset holds t_z, with up to 4 * 4 + 6 = 22 coefficients, in 32 points
+    let field = Field::new(0xffff_ffff_0000_0001);
+    let mut circuit = PythagoreanCircuit::new(field);
+    circuit.build_circuit().unwrap();
+    let pk = ProvingKey::new(circuit.circuit, &srs);
+    let coset = pk.coset.unwrap();
+    assert_eq!(coset.domain.size, 32);
+    assert_eq!(
+        coset.left_copy,
+        Evaluations::from_polynomial_on_coset(
+            &pk.circuit.circuit_polys.left_copy,
+            &coset.domain,
+            coset.shift
+        )
+        .values
+    );
+}
+
+#[test]
+fn test_keys_across_proofs() {
+    use crate::plonk_by_hand::blinding::SeededRng;
+    use crate::plonk_by_hand::prover::{Prover, ProverError};
+    use crate::plonk_by_hand::verifier::verify;
+
+    let field = constants::FIELD_17;
+    let srs = test_utils::srs_by_hand();
+    let (pk, vk) = setup(PythagoreanCircuit::new(field), &srs).unwrap();
+    let prove = |inputs: Vec<u64>| {
+        let mut prover = Prover::from_key(&pk, inputs, srs.copy()).unwrap();
+        prover.set_blinding_rng(Box::new(SeededRng::new(7)));
+        prover.generate_proof()
+    };
+
+    assert_eq!(verify(&vk, &[], &prove(vec![3, 4, 5]).unwrap()), Ok(()));
+    assert_eq!(verify(&vk, &[], &prove(vec![5, 12, 13]).unwrap()), Ok(()));
+    assert!(matches!(
+        prove(vec![3, 4, 6]),
+        Err(ProverError::UnsatisfiedConstraints(_))
+    ));
+}
@@ -1,5 +1,9 @@
 use crate::field::Field;
 use crate::math::ecc::{CurvePoint, ExtensionCurvePoint, ECC};
+use crate::plonk_by_hand::json::{
+    extension_point_from_json, extension_point_to_json, field_from_json, hex, point_from_json,
+    point_to_json, Json, JsonError,
+};
 
 #[derive(Clone)]
 pub struct SRS {
@@ -8,7 +12,7 @@ pub struct SRS {
     pub g_1_points: Vec<CurvePoint>,
     pub g_2_points: Vec<ExtensionCurvePoint>,
     pub degree: u32,
-    pub s: u32,
+    pub s: u64,
     pub scalar_field: Field,
     pub ecc: ECC,
 }
@@ -19,7 +23,7 @@ impl SRS {
         for i in 0..(self.degree + 3) {
             g_1_points.push(
                 self.ecc
-                    .multiply(self.scalar_field.exponent(self.s, i), &self.g_1),
+                    .multiply(self.scalar_field.exponent(self.s, i as u64), &self.g_1),
             );
         }
 
@@ -31,6 +35,73 @@ impl SRS {
         self.g_2_points.push(self.ecc.double_extension(&self.g_2));
     }
 
+    // The public points only. The secret s never leaves this process.
+    pub fn to_json(&self) -> String {
+        Json::object(vec![
+            ("scalar_field", hex(self.scalar_field.order)),
+            ("curve_field", hex(self.ecc.field.order)),
+            ("degree", Json::Number(self.degree as u64)),
+            (
+                "g_1_points",
+                Json::Array(self.g_1_points.iter().map(point_to_json).collect()),
+            ),
+            (
+                "g_2_points",
+                Json::Array(
+                    self.g_2_points
+                        .iter()
+                        .map(extension_point_to_json)
+                        .collect(),
+                ),
+            ),
+        ])
+        .to_pretty_string()
+    }
+
+    // g_1 and g_2 are the first points. s is unknown, so it is left at 0 and the
+    // points must not be generated again.
+    pub fn from_json(text: &str) -> Result<SRS, JsonError> {
+        let json = Json::parse(text)?;
+        let scalar_field = field_from_json(json.get("scalar_field")?, "scalar_field")?;
+        let ecc = ECC {
+            field: field_from_json(json.get("curve_field")?, "curve_field")?,
+        };
+        let degree = u32::try_from(json.get("degree")?.as_u64("degree")?)
+            .map_err(|_| JsonError::WrongType("degree".to_string()))?;
+
+        let g_1_points = json
+            .get("g_1_points")?
+            .as_array("g_1_points")?
+            .iter()
+            .map(|point| point_from_json(point, "g_1_points", &ecc, scalar_field.order))
+            .collect::<Result<Vec<CurvePoint>, JsonError>>()?;
+        let g_2_points = json
+            .get("g_2_points")?
+            .as_array("g_2_points")?
+            .iter()
+            .map(|point| extension_point_from_json(point, "g_2_points", &ecc))
+            .collect::<Result<Vec<ExtensionCurvePoint>, JsonError>>()?;
+
+        // generate_g_1_points makes degree + 3 points
+        if g_1_points.len() != degree as usize + 3 {
+            return Err(JsonError::Inconsistent("g_1_points".to_string()));
+        }
+        if g_2_points.is_empty() {
+            return Err(JsonError::Inconsistent("g_2_points".to_string()));
+        }
+
+        Ok(SRS {
+            g_1: g_1_points[0].clone(),
+            g_2: g_2_points[0].clone(),
+            g_1_points,
+            g_2_points,
+            degree,
+            s: 0,
+            scalar_field,
+            ecc,
+        })
+    }
+
     pub fn copy(&self) -> SRS {
         SRS {
             g_1: self.g_1.clone(),
@@ -39,9 +110,9 @@ impl SRS {
             g_2_points: self.g_2_points.clone(),
             degree: self.degree,
             s: self.s,
-            scalar_field: self.scalar_field.clone(),
+            scalar_field: self.scalar_field,
             ecc: ECC {
-                field: self.ecc.field.clone(),
+                field: self.ecc.field,
             },
         }
     }
@@ -63,7 +134,7 @@ fn test_g_1_points() {
     };
 
     srs.generate_g_1_points();
-    let expected_points = vec![
+    let expected_points = [
         CurvePoint { x: 1, y: 2 },
         CurvePoint { x: 68, y: 74 },
         CurvePoint { x: 65, y: 98 },
@@ -73,8 +144,8 @@ fn test_g_1_points() {
         CurvePoint { x: 65, y: 3 },
     ];
 
-    for i in 0..srs.g_1_points.len() {
-        assert!(CurvePoint::equals(&srs.g_1_points[i], &expected_points[i]));
+    for (point, expected_point) in srs.g_1_points.iter().zip(expected_points.iter()) {
+        assert!(CurvePoint::equals(point, expected_point));
     }
 }
 
@@ -106,3 +177,42 @@ fn test_g_2_points() {
         &expected_second_point
     ));
 }
+
+#[test]
+fn test_srs_json_round_trip() {
+    use crate::plonk_by_hand::constants;
+
+    let mut srs = constants::SRS_BY_HAND;
+    srs.generate_g_1_points();
+    srs.generate_g_2_points();
+
+    let text = srs.to_json();
+    assert!(!text.contains("\"s\""));
+    assert!(text.contains("{ \"x\": \"0x44\", \"y\": \"0x4a\" }"));
+
+    let parsed = SRS::from_json(&text).unwrap();
+    assert_eq!(parsed.to_json(), text);
+    assert_eq!(parsed.degree, 4);
+    assert_eq!(parsed.scalar_field, srs.scalar_field);
+    assert!(CurvePoint::equals(&parsed.g_1, &srs.g_1));
+    assert!(ExtensionCurvePoint::equals(&parsed.g_2, &srs.g_2));
+
+    let truncated = text.replacen("{ \"x\": \"0x44\", \"y\": \"0x4a\" },", "", 1);
+    assert_eq!(
+        SRS::from_json(&truncated).err(),
+        Some(JsonError::Inconsistent("g_1_points".to_string()))
+    );
+
+    let zero_curve_field =
+        text.replacen("\"curve_field\": \"0x65\"", "\"curve_field\": \"0x0\"", 1);
+    assert_eq!(
+        SRS::from_json(&zero_curve_field).err(),
+        Some(JsonError::InvalidField("curve_field".to_string()))
+    );
+
+    let off_curve = text.replacen("\"y\": \"0x1f\"", "\"y\": \"0x20\"", 1);
+    assert_eq!(
+        SRS::from_json(&off_curve).err(),
+        Some(JsonError::PointNotOnCurve("g_2_points".to_string()))
+    );
+}
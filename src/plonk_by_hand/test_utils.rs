@@ -0,0 +1,44 @@
+use crate::plonk_by_hand::blinding::SeededRng;
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
+use crate::plonk_by_hand::constants;
+use crate::plonk_by_hand::proof::Proof;
+use crate::plonk_by_hand::prover::{Prover, ProverError};
+use crate::plonk_by_hand::setup::setup;
+use crate::plonk_by_hand::structured_reference_string::SRS;
+use crate::plonk_by_hand::verifier::{verify, VerifyingKey};
+
+// The article's SRS with its G1 and G2 points generated
+pub fn srs_by_hand() -> SRS {
+    let mut srs = constants::SRS_BY_HAND;
+    srs.generate_g_1_points();
+    srs.generate_g_2_points();
+    srs
+}
+
+// Sets the circuit up over the article's SRS and proves it with seeded blinding
+pub fn prove<C: CircuitDefinition>(
+    circuit: C,
+    inputs: Vec<u64>,
+    seed: u64,
+) -> (VerifyingKey, Result<Proof, ProverError>) {
+    let srs = srs_by_hand();
+    let (pk, vk) = setup(circuit, &srs).unwrap();
+
+    let proof = Prover::from_key(&pk, inputs, srs).and_then(|mut prover| {
+        prover.set_blinding_rng(Box::new(SeededRng::new(seed)));
+        prover.generate_proof()
+    });
+    (vk, proof)
+}
+
+// Proves and checks the proof, false if either side refuses
+pub fn prove_and_verify<C: CircuitDefinition>(
+    circuit: C,
+    inputs: Vec<u64>,
+    public_inputs: &[u64],
+    seed: u64,
+) -> bool {
+    let (vk, proof) = prove(circuit, inputs, seed);
+    // an unsatisfied witness is refused before any proof exists
+    proof.is_ok_and(|proof| verify(&vk, public_inputs, &proof).is_ok())
+}
@@ -0,0 +1,187 @@
+use crate::math::ecc::CurvePoint;
+use crate::math::field::{Field, FieldElement};
+use crate::math::roots_of_unity::EvaluationDomain;
+use crate::math::sha256::Sha256;
+#[cfg(test)]
+use crate::plonk_by_hand::public_coin::PublicCoin;
+
+pub const TRANSCRIPT_LABEL: &[u8] = b"plonk_by_hand";
+
+// Fiat-Shamir transcript. The prover and verifier absorb the same messages in the
+// same order, so they squeeze the same challenges without any interaction.
+pub struct Transcript {
+    field: Field,
+    mode: TranscriptMode,
+}
+
+enum TranscriptMode {
+    // every absorbed message, with each squeezed digest fed back in
+    Hashed(Sha256),
+    // replays the challenges from the plonk_by_hand article so its worked values can
+    // be checked. Not sound: the challenges ignore the messages, so tests only.
+    #[cfg(test)]
+    Fixed(Box<PublicCoin>),
+}
+
+impl Transcript {
+    pub fn new(label: &[u8], field: Field) -> Transcript {
+        let mut transcript = Transcript {
+            field,
+            mode: TranscriptMode::Hashed(Sha256::new()),
+        };
+        transcript.append_message(b"protocol", label);
+        transcript.append_message(b"field", &field.order.to_be_bytes());
+
+        transcript
+    }
+
+    #[cfg(test)]
+    pub fn fixed(public_coin: PublicCoin, field: Field) -> Transcript {
+        Transcript {
+            field,
+            mode: TranscriptMode::Fixed(Box::new(public_coin)),
+        }
+    }
+
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        match &mut self.mode {
+            TranscriptMode::Hashed(state) => {
+                // length prefixes keep ("ab", "c") and ("a", "bc") apart
+                state.update(&(label.len() as u64).to_be_bytes());
+                state.update(label);
+                state.update(&(message.len() as u64).to_be_bytes());
+                state.update(message);
+            }
+            #[cfg(test)]
+            TranscriptMode::Fixed(_) => {}
+        }
+    }
+
+    pub fn append_point(&mut self, label: &[u8], point: &CurvePoint) {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&point.x.to_be_bytes());
+        bytes[8..].copy_from_slice(&point.y.to_be_bytes());
+        self.append_message(label, &bytes);
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: FieldElement) {
+        self.append_message(label, &scalar.value.to_be_bytes());
+    }
+
+    // A non-zero challenge. Zero would make beta, gamma, alpha, v or u useless.
+    pub fn challenge_scalar(&mut self, label: &'static str) -> FieldElement {
+        self.challenge_where(label, |challenge| !challenge.is_zero())
+    }
+
+    // The evaluation point zeta must lie outside H, or Z_H(zeta) = 0 and the verifier
+    // would divide by zero
+    pub fn challenge_outside_domain(
+        &mut self,
+        label: &'static str,
+        domain: &EvaluationDomain,
+    ) -> FieldElement {
+        self.challenge_where(label, |challenge| {
+            !domain.evaluate_vanishing_polynomial(challenge).is_zero()
+        })
+    }
+
+    // Squeezes until accept holds. Both parties run the same loop, so re-squeezing
+    // stays deterministic.
+    fn challenge_where(
+        &mut self,
+        label: &'static str,
+        accept: impl Fn(FieldElement) -> bool,
+    ) -> FieldElement {
+        let field = self.field;
+        match &mut self.mode {
+            #[cfg(test)]
+            TranscriptMode::Fixed(public_coin) => Transcript::fixed_challenge(public_coin, label),
+            TranscriptMode::Hashed(state) => loop {
+                state.update(b"challenge");
+                state.update(label.as_bytes());
+                let digest = state.finalize();
+                state.update(&digest);
+
+                let challenge = Transcript::reduce(&digest, field);
+                if accept(challenge) {
+                    break challenge;
+                }
+            },
+        }
+    }
+
+    // Reads the digest as a big endian integer mod p. The bias is negligible for
+    // 64-bit fields, and harmless for the toy ones.
+    fn reduce(digest: &[u8; 32], field: Field) -> FieldElement {
+        let order = field.order as u128;
+        let value = digest
+            .iter()
+            .fold(0u128, |acc, byte| (acc * 256 + *byte as u128) % order);
+
+        field.element(value as u64)
+    }
+
+    #[cfg(test)]
+    fn fixed_challenge(public_coin: &PublicCoin, label: &str) -> FieldElement {
+        match label {
+            "beta" => public_coin.beta,
+            "gamma" => public_coin.gamma,
+            "alpha" => public_coin.alpha,
+            "zeta" => public_coin.zed,
+            "v" => public_coin.v,
+            "u" => public_coin.u,
+            _ => panic!("no fixed value for challenge {}", label),
+        }
+    }
+}
+
+#[test]
+fn test_transcript_is_deterministic() {
+    let field = Field::new(0xffff_ffff_0000_0001);
+
+    let mut prover_transcript = Transcript::new(b"test", field);
+    let mut verifier_transcript = Transcript::new(b"test", field);
+    for transcript in [&mut prover_transcript, &mut verifier_transcript] {
+        transcript.append_point(b"a", &CurvePoint::new(91, 66));
+        transcript.append_scalar(b"a_bar", field.element(15));
+    }
+    let beta = prover_transcript.challenge_scalar("beta");
+    assert_eq!(beta, verifier_transcript.challenge_scalar("beta"));
+
+    // squeezing again gives a fresh challenge
+    let gamma = prover_transcript.challenge_scalar("gamma");
+    assert_ne!(beta, gamma);
+
+    // a different message gives a different challenge
+    let mut other_transcript = Transcript::new(b"test", field);
+    other_transcript.append_point(b"a", &CurvePoint::new(91, 35));
+    other_transcript.append_scalar(b"a_bar", field.element(15));
+    assert_ne!(beta, other_transcript.challenge_scalar("beta"));
+}
+
+#[test]
+fn test_challenge_outside_domain() {
+    let field = Field::new(17);
+    let domain = EvaluationDomain::new(4, field).unwrap();
+
+    let mut transcript = Transcript::new(b"test", field);
+    for _ in 0..50 {
+        let zeta = transcript.challenge_outside_domain("zeta", &domain);
+        assert!(!domain.elements.contains(&zeta));
+        assert!(!transcript.challenge_scalar("v").is_zero());
+    }
+}
+
+#[test]
+fn test_fixed_transcript() {
+    use crate::plonk_by_hand::constants;
+
+    let field = constants::FIELD_17;
+    let domain = EvaluationDomain::new(4, field).unwrap();
+    let mut transcript = Transcript::fixed(constants::PUB_COIN.clone(), field);
+    transcript.append_point(b"a", &CurvePoint::new(91, 66));
+
+    assert_eq!(transcript.challenge_scalar("beta"), 12);
+    assert_eq!(transcript.challenge_scalar("gamma"), 13);
+    assert_eq!(transcript.challenge_outside_domain("zeta", &domain), 5);
+}
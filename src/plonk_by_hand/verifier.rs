@@ -1,26 +1,95 @@
-use crate::field::Field;
+use crate::field::{Field, FieldElement};
 use crate::math::complex_scalar::ComplexScalar;
-use crate::math::ecc::ECC;
+use crate::math::ecc::{ExtensionCurvePoint, ECC};
 use crate::math::pairing;
 use crate::math::polynomial::Polynomial;
+use crate::math::roots_of_unity::{DomainError, EvaluationDomain};
+#[cfg(test)]
+use crate::plonk_by_hand::blinding::{FixedBlinding, SeededRng};
+use crate::plonk_by_hand::circuit::Circuit;
+use crate::plonk_by_hand::circuit_definition::CircuitDefinition;
+#[cfg(test)]
 use crate::plonk_by_hand::constants;
+use crate::plonk_by_hand::json::{
+    extension_point_from_json, extension_point_to_json, field_from_json, hex, point_from_json,
+    point_to_json, scalar_from_json, scalar_to_json, Json, JsonError,
+};
 use crate::plonk_by_hand::proof::Proof;
 use crate::plonk_by_hand::public_coin::PublicCoin;
+use crate::plonk_by_hand::setup::setup;
 use crate::plonk_by_hand::structured_reference_string::SRS;
-use crate::{CurvePoint, Prover, PythagoreanCircuit};
+#[cfg(test)]
+use crate::plonk_by_hand::test_utils;
+use crate::plonk_by_hand::transcript::{Transcript, TRANSCRIPT_LABEL};
+use crate::CurvePoint;
+#[cfg(test)]
+use crate::Prover;
+#[cfg(test)]
+use crate::PythagoreanCircuit;
 
 pub struct Verifier {
-    pub circuit: PythagoreanCircuit,
-    pub srs: SRS,
-    pub ecc: ECC,
-    pub field: Field,
+    pub vk: VerifyingKey,
     pub pub_coin: PublicCoin,
     pub proof: Proof,
-    pub commitments: VerifierCommitments,
+    pub public_inputs: Vec<u64>,
     pub vals: VerifierVals,
+    fixed_challenges: bool,
 }
 
-#[derive(Default)]
+// Everything the verifier needs from the circuit and the SRS, computed once by
+// preprocessing. It never changes, so one key can check any number of proofs.
+#[derive(Clone)]
+pub struct VerifyingKey {
+    pub ecc: ECC,
+    pub field: Field,
+    pub domain: EvaluationDomain,
+    pub k1: FieldElement,
+    pub k2: FieldElement,
+    pub public_input_gates: Vec<usize>,
+    pub commitments: VerifierCommitments,
+    pub g_1: CurvePoint,
+    pub g_2_points: Vec<ExtensionCurvePoint>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    // a commitment in the proof is neither on the curve nor the point at infinity
+    PointNotOnCurve {
+        name: &'static str,
+        x: u64,
+        y: u64,
+    },
+    // a point on the curve that g_1 does not generate, e.g. one of small order
+    PointNotInSubgroup {
+        name: &'static str,
+        x: u64,
+        y: u64,
+    },
+    // an opening is not reduced mod the scalar field order
+    OpeningOutOfRange {
+        name: &'static str,
+        value: u64,
+    },
+    // an opening does not belong to the scalar field at all, e.g. no proof was provided
+    MalformedProof {
+        name: &'static str,
+    },
+    WrongPublicInputCount {
+        expected: usize,
+        actual: usize,
+    },
+    PublicInputOutOfRange {
+        index: usize,
+        value: u64,
+    },
+    // e([W] + u [Wz], [s]) against e(z [W] + u z ω [Wz] + [F] - [E], [1])
+    PairingMismatch {
+        left: ComplexScalar,
+        right: ComplexScalar,
+    },
+}
+
+#[derive(Default, Clone)]
 pub struct VerifierCommitments {
     pub left_selector: CurvePoint,
     pub right_selector: CurvePoint,
@@ -32,538 +101,742 @@ pub struct VerifierCommitments {
     pub output_copy: CurvePoint,
 }
 
+impl VerifierCommitments {
+    // In the order they are exported and absorbed into the transcript
+    pub fn named(&self) -> [(&'static str, &CurvePoint); 8] {
+        [
+            ("left_selector", &self.left_selector),
+            ("right_selector", &self.right_selector),
+            ("output_selector", &self.output_selector),
+            ("multiply_selector", &self.multiply_selector),
+            ("c_selector", &self.c_selector),
+            ("left_copy", &self.left_copy),
+            ("right_copy", &self.right_copy),
+            ("output_copy", &self.output_copy),
+        ]
+    }
+}
+
 #[derive(Default)]
 pub struct VerifierVals {
-    pub z_h_opening: u32,
-    pub lagrange_1_opening: u32,
-    pub t_opening: u32,
+    pub z_h_opening: FieldElement,
+    pub lagrange_1_opening: FieldElement,
+    pub public_input_opening: FieldElement,
+    pub t_opening: FieldElement,
     pub d_commitment: CurvePoint,
     pub f_commitment: CurvePoint,
     pub e_commitment: CurvePoint,
 }
 
-impl Verifier {
-    pub fn new(field: Field, srs: SRS) -> Verifier {
-        let mut py_circuit = PythagoreanCircuit::new(field.clone());
-        py_circuit.build_circuit();
-        let ecc = ECC {
-            field: Field { order: 101 },
+impl VerifyingKey {
+    // Commits to the selector and copy polynomials of a circuit whose gates are built
+    pub fn new(circuit: &Circuit, srs: &SRS) -> VerifyingKey {
+        let circuit_polys = &circuit.circuit_polys;
+        let commit_poly = |poly: &Polynomial| {
+            let mut res = CurvePoint::point_at_infinity();
+            for (i, coefficient) in poly.coefficients().iter().enumerate() {
+                res = srs.ecc.add(
+                    &res,
+                    &srs.ecc.multiply(coefficient.value, &srs.g_1_points[i]),
+                );
+            }
+            res
         };
 
-        Verifier {
-            circuit: py_circuit,
-            srs,
-            ecc,
-            field,
-            pub_coin: Default::default(),
-            proof: Default::default(),
-            commitments: Default::default(),
-            vals: Default::default(),
+        VerifyingKey {
+            ecc: srs.ecc.clone(),
+            field: circuit.field,
+            domain: circuit.domain.clone(),
+            k1: circuit.k1,
+            k2: circuit.k2,
+            public_input_gates: circuit.public_input_gates.clone(),
+            commitments: VerifierCommitments {
+                left_selector: commit_poly(&circuit_polys.left_selector),
+                right_selector: commit_poly(&circuit_polys.right_selector),
+                output_selector: commit_poly(&circuit_polys.output_selector),
+                multiply_selector: commit_poly(&circuit_polys.multiply_selector),
+                c_selector: commit_poly(&circuit_polys.constant_selector),
+                left_copy: commit_poly(&circuit_polys.left_copy),
+                right_copy: commit_poly(&circuit_polys.right_copy),
+                output_copy: commit_poly(&circuit_polys.output_copy),
+            },
+            g_1: srs.g_1.clone(),
+            g_2_points: srs.g_2_points.clone(),
         }
     }
 
-    pub fn verify_proof(&mut self) -> bool {
-        // Step 1
-        let mut verified = self.verify_commitments_on_curve();
+    // Starts the transcript both parties replay. The whole key goes in before the
+    // public inputs and the proof, so the challenges are bound to this circuit.
+    pub fn transcript(&self, public_inputs: impl IntoIterator<Item = FieldElement>) -> Transcript {
+        let mut transcript = Transcript::new(TRANSCRIPT_LABEL, self.field);
+        transcript.append_message(b"curve_field", &self.ecc.field.order.to_be_bytes());
+        transcript.append_message(b"n", &(self.domain.size as u64).to_be_bytes());
+        transcript.append_scalar(b"generator", self.domain.generator);
+        transcript.append_scalar(b"k1", self.k1);
+        transcript.append_scalar(b"k2", self.k2);
+        for row in self.public_input_gates.iter() {
+            transcript.append_message(b"public_input_gate", &(*row as u64).to_be_bytes());
+        }
+        for (name, point) in self.commitments.named() {
+            transcript.append_point(name.as_bytes(), point);
+        }
+        transcript.append_point(b"g_1", &self.g_1);
+        // check_pairing reads [1]_2 and [s]_2
+        for point in self.g_2_points.iter().take(2) {
+            let mut bytes = [0u8; 17];
+            bytes[..8].copy_from_slice(&point.x.to_be_bytes());
+            bytes[8..16].copy_from_slice(&point.y.to_be_bytes());
+            bytes[16] = point.u as u8;
+            transcript.append_message(b"g_2", &bytes);
+        }
 
-        // Step 2
-        verified = verified && self.verify_openings_in_field();
+        for input in public_inputs {
+            transcript.append_scalar(b"public_input", input);
+        }
+        transcript
+    }
 
-        // Skip step 3 since no public inputs
+    pub fn to_json(&self) -> String {
+        let named_commitments = self.commitments.named();
+
+        Json::object(vec![
+            ("scalar_field", hex(self.field.order)),
+            ("domain_size", Json::Number(self.domain.size as u64)),
+            ("generator", scalar_to_json(self.domain.generator)),
+            ("k1", scalar_to_json(self.k1)),
+            ("k2", scalar_to_json(self.k2)),
+            (
+                "public_input_gates",
+                Json::Array(
+                    self.public_input_gates
+                        .iter()
+                        .map(|row| Json::Number(*row as u64))
+                        .collect(),
+                ),
+            ),
+            (
+                "commitments",
+                Json::object(
+                    named_commitments
+                        .iter()
+                        .map(|(name, point)| (*name, point_to_json(point)))
+                        .collect(),
+                ),
+            ),
+            ("g_1", point_to_json(&self.g_1)),
+            (
+                "g_2_points",
+                Json::Array(
+                    self.g_2_points
+                        .iter()
+                        .map(extension_point_to_json)
+                        .collect(),
+                ),
+            ),
+        ])
+        .to_pretty_string()
+    }
 
-        // Step 4
-        self.set_z_h_opening();
+    // The domain is rebuilt from its size, and must have the exported generator
+    pub fn from_json(text: &str, ecc: &ECC) -> Result<VerifyingKey, JsonError> {
+        let json = Json::parse(text)?;
+        let field = field_from_json(json.get("scalar_field")?, "scalar_field")?;
+        let inconsistent = |name: &str| JsonError::Inconsistent(name.to_string());
 
-        // Step 5
-        self.set_lagrange_1_opening();
+        let size = json.get("domain_size")?.as_u64("domain_size")? as usize;
+        let domain = EvaluationDomain::new(size, field).map_err(|_| inconsistent("domain_size"))?;
+        if scalar_from_json(json.get("generator")?, "generator", field)? != domain.generator {
+            return Err(inconsistent("generator"));
+        }
 
-        // Skip step 6 since no public inputs
+        let public_input_gates = json
+            .get("public_input_gates")?
+            .as_array("public_input_gates")?
+            .iter()
+            .map(|row| match row.as_u64("public_input_gates")? as usize {
+                row if row < size => Ok(row),
+                _ => Err(inconsistent("public_input_gates")),
+            })
+            .collect::<Result<Vec<usize>, JsonError>>()?;
+
+        let json_commitments = json.get("commitments")?;
+        let commitment =
+            |name: &str| point_from_json(json_commitments.get(name)?, name, ecc, field.order);
+        let commitments = VerifierCommitments {
+            left_selector: commitment("left_selector")?,
+            right_selector: commitment("right_selector")?,
+            output_selector: commitment("output_selector")?,
+            multiply_selector: commitment("multiply_selector")?,
+            c_selector: commitment("c_selector")?,
+            left_copy: commitment("left_copy")?,
+            right_copy: commitment("right_copy")?,
+            output_copy: commitment("output_copy")?,
+        };
 
-        // Step 7
-        self.set_t_opening();
+        // check_pairing reads the first two
+        let g_2_points = json
+            .get("g_2_points")?
+            .as_array("g_2_points")?
+            .iter()
+            .map(|point| extension_point_from_json(point, "g_2_points", ecc))
+            .collect::<Result<Vec<ExtensionCurvePoint>, JsonError>>()?;
+        if g_2_points.len() < 2 {
+            return Err(inconsistent("g_2_points"));
+        }
 
-        // Step 8
-        self.set_d_commitment();
+        // the copy constraints only hold if H, k1 * H and k2 * H do not overlap
+        let k1 = scalar_from_json(json.get("k1")?, "k1", field)?;
+        let k2 = scalar_from_json(json.get("k2")?, "k2", field)?;
+        if !domain.is_coset_shift(k1) {
+            return Err(inconsistent("k1"));
+        }
+        if !domain.are_coset_shifts(k1, k2) {
+            return Err(inconsistent("k2"));
+        }
 
-        // Step 9
-        self.set_f_commitment();
+        Ok(VerifyingKey {
+            ecc: ecc.clone(),
+            field,
+            k1,
+            k2,
+            domain,
+            public_input_gates,
+            commitments,
+            g_1: point_from_json(json.get("g_1")?, "g_1", ecc, field.order)?,
+            g_2_points,
+        })
+    }
+}
 
-        // Step 10
-        self.set_e_commitment();
+// Checks a proof against a verifying key. Borrows both and keeps no state between
+// calls, so one key can check proofs from any number of threads.
+pub fn verify(
+    vk: &VerifyingKey,
+    public_inputs: &[u64],
+    proof: &Proof,
+) -> Result<(), VerificationError> {
+    // Steps 1 to 3
+    vk.verify_commitments_on_curve(proof)?;
+    vk.verify_openings_in_field(proof)?;
+    vk.verify_public_inputs(public_inputs)?;
+
+    let coin = vk.challenges(public_inputs, proof);
+
+    // Steps 4 to 7
+    let mut vals = VerifierVals {
+        z_h_opening: vk.z_h_opening(coin.zed),
+        lagrange_1_opening: vk.lagrange_1_opening(coin.zed),
+        public_input_opening: vk.public_input_opening(public_inputs, coin.zed),
+        ..Default::default()
+    };
+    vals.t_opening = t_opening(proof, &coin, &vals);
+
+    // Steps 8 to 10
+    vals.d_commitment = vk.d_commitment(proof, &coin, &vals);
+    vals.f_commitment = vk.f_commitment(proof, &coin, &vals);
+    vals.e_commitment = vk.e_commitment(proof, &coin, &vals);
+
+    // Step 11
+    vk.check_pairing(proof, &coin, &vals)
+}
 
-        // Step 11
-        verified && self.check_pairing()
+// The verifier's steps, each reading the proof and the values of the steps before it
+impl VerifyingKey {
+    // A point outside the order r subgroup has no discrete log to the base g_1,
+    // so it is rejected here rather than reaching the pairing
+    pub fn verify_commitments_on_curve(&self, proof: &Proof) -> Result<(), VerificationError> {
+        for (name, point) in proof.commitments() {
+            let (x, y) = (point.x, point.y);
+            if !self.in_curve(point) {
+                return Err(VerificationError::PointNotOnCurve { name, x, y });
+            }
+            if !self.ecc.in_subgroup(point, self.field.order) {
+                return Err(VerificationError::PointNotInSubgroup { name, x, y });
+            }
+        }
+        Ok(())
     }
 
-    pub fn provide_proof(&mut self, pub_coin: PublicCoin, proof: Proof) {
-        self.pub_coin = pub_coin;
-        self.proof = proof;
+    pub fn verify_openings_in_field(&self, proof: &Proof) -> Result<(), VerificationError> {
+        for (name, opening) in proof.openings() {
+            if opening.field != self.field {
+                return Err(VerificationError::MalformedProof { name });
+            }
+            if opening.value >= self.field.order {
+                return Err(VerificationError::OpeningOutOfRange {
+                    name,
+                    value: opening.value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn verify_public_inputs(&self, public_inputs: &[u64]) -> Result<(), VerificationError> {
+        let expected = self.public_input_gates.len();
+        if public_inputs.len() != expected {
+            return Err(VerificationError::WrongPublicInputCount {
+                expected,
+                actual: public_inputs.len(),
+            });
+        }
+
+        match public_inputs
+            .iter()
+            .position(|input| *input >= self.field.order)
+        {
+            Some(index) => Err(VerificationError::PublicInputOutOfRange {
+                index,
+                value: public_inputs[index],
+            }),
+            None => Ok(()),
+        }
     }
 
-    pub fn preprocess(&mut self) {
-        self.commitments = VerifierCommitments {
-            left_selector: self.commit_poly(&self.circuit.circuit.circuit_polys.left_selector),
-            right_selector: self.commit_poly(&self.circuit.circuit.circuit_polys.right_selector),
-            output_selector: self.commit_poly(&self.circuit.circuit.circuit_polys.output_selector),
-            multiply_selector: self
-                .commit_poly(&self.circuit.circuit.circuit_polys.multiply_selector),
-            c_selector: CurvePoint::point_at_infinity(),
-            left_copy: self.commit_poly(&self.circuit.circuit.circuit_polys.left_copy),
-            right_copy: self.commit_poly(&self.circuit.circuit.circuit_polys.right_copy),
-            output_copy: self.commit_poly(&self.circuit.circuit.circuit_polys.output_copy),
+    // Replays the prover's transcript over the proof to recompute every challenge
+    pub fn challenges(&self, public_inputs: &[u64], proof: &Proof) -> PublicCoin {
+        let mut transcript =
+            self.transcript(public_inputs.iter().map(|input| self.field.element(*input)));
+
+        transcript.append_point(b"a", &proof.a);
+        transcript.append_point(b"b", &proof.b);
+        transcript.append_point(b"c", &proof.c);
+        let beta = transcript.challenge_scalar("beta");
+        let gamma = transcript.challenge_scalar("gamma");
+
+        transcript.append_point(b"z", &proof.z);
+        let alpha = transcript.challenge_scalar("alpha");
+
+        transcript.append_point(b"t_lo", &proof.t_lo);
+        transcript.append_point(b"t_mid", &proof.t_mid);
+        transcript.append_point(b"t_hi", &proof.t_hi);
+        let zed = transcript.challenge_outside_domain("zeta", &self.domain);
+
+        transcript.append_scalar(b"a_bar", proof.a_bar);
+        transcript.append_scalar(b"b_bar", proof.b_bar);
+        transcript.append_scalar(b"c_bar", proof.c_bar);
+        transcript.append_scalar(b"left_copy_bar", proof.left_copy_bar);
+        transcript.append_scalar(b"right_copy_bar", proof.right_copy_bar);
+        transcript.append_scalar(b"r_bar", proof.r_bar);
+        transcript.append_scalar(b"z_bar", proof.z_bar);
+        let v = transcript.challenge_scalar("v");
+
+        transcript.append_point(b"w", &proof.w);
+        transcript.append_point(b"wz", &proof.wz);
+        let u = transcript.challenge_scalar("u");
+
+        PublicCoin {
+            alpha,
+            beta,
+            gamma,
+            zed,
+            v,
+            u,
         }
     }
 
-    pub fn verify_commitments_on_curve(&self) -> bool {
-        self.in_curve(&self.proof.a)
-            && self.in_curve(&self.proof.b)
-            && self.in_curve(&self.proof.c)
-            && self.in_curve(&self.proof.z)
-            && self.in_curve(&self.proof.t_lo)
-            && self.in_curve(&self.proof.t_mid)
-            && self.in_curve(&self.proof.t_hi)
-            && self.in_curve(&self.proof.w)
-            && self.in_curve(&self.proof.wz)
+    pub fn z_h_opening(&self, zed: FieldElement) -> FieldElement {
+        self.domain.evaluate_vanishing_polynomial(zed)
     }
 
-    pub fn verify_openings_in_field(&self) -> bool {
-        self.in_scalar_field(self.proof.a_bar)
-            && self.in_scalar_field(self.proof.b_bar)
-            && self.in_scalar_field(self.proof.c_bar)
-            && self.in_scalar_field(self.proof.left_copy_bar)
-            && self.in_scalar_field(self.proof.right_copy_bar)
-            && self.in_scalar_field(self.proof.r_bar)
-            && self.in_scalar_field(self.proof.z_bar)
+    pub fn lagrange_1_opening(&self, zed: FieldElement) -> FieldElement {
+        self.lagrange_opening(0, zed)
     }
 
-    pub fn set_z_h_opening(&mut self) {
-        self.vals.z_h_opening = self
-            .circuit
-            .circuit
-            .circuit_polys
-            .z_h
-            .eval(self.pub_coin.zed);
+    // PI(zeta) = sum of -x_i * L_i(zeta) over the public input rows, without
+    // interpolating
+    pub fn public_input_opening(&self, public_inputs: &[u64], zed: FieldElement) -> FieldElement {
+        self.public_input_gates
+            .iter()
+            .zip(public_inputs)
+            .fold(self.field.zero(), |sum, (row, input)| {
+                sum - self.field.element(*input) * self.lagrange_opening(*row, zed)
+            })
     }
 
-    pub fn set_lagrange_1_opening(&mut self) {
-        self.vals.lagrange_1_opening = Polynomial::get_lagrange_1_poly(
-            &self.field,
-            (self.circuit.circuit.roots.len() - 1) as u32,
-            &self.circuit.circuit.roots,
-        )
-        .eval(self.pub_coin.zed);
+    // L_i(zeta) = ω^i * (zeta^n - 1) / (n * (zeta - ω^i)), and 1 or 0 on the domain
+    fn lagrange_opening(&self, row: usize, zed: FieldElement) -> FieldElement {
+        let root = self.domain.elements[row];
+        match (zed - root).inverse() {
+            Some(inverse) => root * self.z_h_opening(zed) * self.domain.size_inv * inverse,
+            None => self.field.one(),
+        }
     }
 
-    pub fn set_t_opening(&mut self) {
-        let first_term = self.field.add(
-            self.proof.a_bar,
-            self.field.add(
-                self.field
-                    .multiply(self.pub_coin.beta, self.proof.left_copy_bar),
-                self.pub_coin.gamma,
-            ),
+    pub fn d_commitment(
+        &self,
+        proof: &Proof,
+        coin: &PublicCoin,
+        vals: &VerifierVals,
+    ) -> CurvePoint {
+        let ecc = &self.ecc;
+        let commitments = &self.commitments;
+
+        let first_term = ecc.multiply(
+            (proof.a_bar * proof.b_bar * coin.v).value,
+            &commitments.multiply_selector,
         );
+        let second_term = ecc.multiply((proof.a_bar * coin.v).value, &commitments.left_selector);
+        let third_term = ecc.multiply((proof.b_bar * coin.v).value, &commitments.right_selector);
+        let fourth_term = ecc.multiply((proof.c_bar * coin.v).value, &commitments.output_selector);
+        let fifth_term = ecc.multiply(coin.v.value, &commitments.c_selector);
 
-        let second_term = self.field.add(
-            self.proof.b_bar,
-            self.field.add(
-                self.field
-                    .multiply(self.pub_coin.beta, self.proof.right_copy_bar),
-                self.pub_coin.gamma,
+        let mut res = ecc.add(
+            &first_term,
+            &ecc.add(
+                &second_term,
+                &ecc.add(&third_term, &ecc.add(&fourth_term, &fifth_term)),
             ),
         );
 
-        let third_term = self.field.add(self.proof.c_bar, self.pub_coin.gamma);
+        let middle_term_scalar = (proof.a_bar + coin.beta * coin.zed + coin.gamma)
+            * (proof.b_bar + coin.beta * self.k1 * coin.zed + coin.gamma)
+            * (proof.c_bar + coin.beta * self.k2 * coin.zed + coin.gamma)
+            * coin.alpha
+            * coin.v
+            + vals.lagrange_1_opening * coin.alpha.pow(2) * coin.v
+            + coin.u;
+        let middle_term = ecc.multiply(middle_term_scalar.value, &proof.z);
+
+        res = ecc.add(&res, &middle_term);
+
+        let last_term_scalar = -((proof.a_bar + coin.beta * proof.left_copy_bar + coin.gamma)
+            * (proof.b_bar + coin.beta * proof.right_copy_bar + coin.gamma)
+            * coin.alpha
+            * coin.v
+            * coin.beta
+            * proof.z_bar);
+        let last_term = ecc.multiply(last_term_scalar.value, &commitments.output_copy);
+
+        ecc.add(&res, &last_term)
+    }
 
-        let middle_term = self.field.multiply(
-            first_term,
-            self.field.multiply(
-                second_term,
-                self.field.multiply(
-                    third_term,
-                    self.field.multiply(self.proof.z_bar, self.pub_coin.alpha),
-                ),
-            ),
+    pub fn f_commitment(
+        &self,
+        proof: &Proof,
+        coin: &PublicCoin,
+        vals: &VerifierVals,
+    ) -> CurvePoint {
+        let ecc = &self.ecc;
+        let zed = coin.zed;
+        let v = coin.v;
+        let n = self.domain.size as u64;
+
+        let mut first_term = ecc.add(
+            &proof.t_lo,
+            &ecc.multiply(zed.pow(n + 2).value, &proof.t_mid),
+        );
+        first_term = ecc.add(
+            &first_term,
+            &ecc.multiply(zed.pow((2 * n) + 4).value, &proof.t_hi),
         );
 
-        let mut res = self
-            .field
-            .add(self.proof.r_bar, self.field.additive_inverse(middle_term));
-        res = self.field.add(
-            res,
-            self.field.additive_inverse(self.field.multiply(
-                self.vals.lagrange_1_opening,
-                self.field.exponent(self.pub_coin.alpha, 2),
-            )),
+        let second_term = &vals.d_commitment;
+
+        let third_term = ecc.add(
+            &ecc.multiply(v.pow(2).value, &proof.a),
+            &ecc.add(
+                &ecc.multiply(v.pow(3).value, &proof.b),
+                &ecc.add(
+                    &ecc.multiply(v.pow(4).value, &proof.c),
+                    &ecc.add(
+                        &ecc.multiply(v.pow(5).value, &self.commitments.left_copy),
+                        &ecc.multiply(v.pow(6).value, &self.commitments.right_copy),
+                    ),
+                ),
+            ),
         );
 
-        res = self.field.divide(res, self.vals.z_h_opening);
+        ecc.add(&first_term, &ecc.add(second_term, &third_term))
+    }
 
-        self.vals.t_opening = res;
+    pub fn e_commitment(
+        &self,
+        proof: &Proof,
+        coin: &PublicCoin,
+        vals: &VerifierVals,
+    ) -> CurvePoint {
+        let v = coin.v;
+
+        let scalar_term = vals.t_opening
+            + v * proof.r_bar
+            + v.pow(2) * proof.a_bar
+            + v.pow(3) * proof.b_bar
+            + v.pow(4) * proof.c_bar
+            + v.pow(5) * proof.left_copy_bar
+            + v.pow(6) * proof.right_copy_bar
+            + coin.u * proof.z_bar;
+
+        self.ecc.multiply(scalar_term.value, &self.g_1)
     }
 
-    pub fn set_d_commitment(&mut self) {
-        let first_term = self.ecc.multiply(
-            self.field.multiply(
-                self.proof.a_bar,
-                self.field.multiply(self.proof.b_bar, self.pub_coin.v),
-            ),
-            &self.commitments.multiply_selector,
-        );
+    pub fn check_pairing(
+        &self,
+        proof: &Proof,
+        coin: &PublicCoin,
+        vals: &VerifierVals,
+    ) -> Result<(), VerificationError> {
+        let ecc = &self.ecc;
 
-        let second_term = self.ecc.multiply(
-            self.field.multiply(self.proof.a_bar, self.pub_coin.v),
-            &self.commitments.left_selector,
-        );
+        let left_first_term = ecc.add(&proof.w, &ecc.multiply(coin.u.value, &proof.wz));
 
-        let third_term = self.ecc.multiply(
-            self.field.multiply(self.proof.b_bar, self.pub_coin.v),
-            &self.commitments.right_selector,
+        let mut right_first_term = ecc.add(
+            &ecc.multiply(coin.zed.value, &proof.w),
+            &ecc.multiply((coin.u * coin.zed * self.domain.generator).value, &proof.wz),
         );
 
-        let fourth_term = self.ecc.multiply(
-            self.field.multiply(self.proof.c_bar, self.pub_coin.v),
-            &self.commitments.output_selector,
+        right_first_term = ecc.add(
+            &right_first_term,
+            &ecc.add(&vals.f_commitment, &ecc.inversion(&vals.e_commitment)),
         );
 
-        let fifth_term = self
-            .ecc
-            .multiply(self.pub_coin.v, &self.commitments.c_selector);
-
-        let mut res = self.ecc.add(
-            &first_term,
-            &self.ecc.add(
-                &second_term,
-                &self
-                    .ecc
-                    .add(&third_term, &self.ecc.add(&fourth_term, &fifth_term)),
-            ),
-        );
+        // never exponent 0 for a point g_1 does not generate, or both sides would be 1
+        let multiple = |name, point: &CurvePoint| {
+            ecc.get_generator_multiple(point, &self.g_1, self.field.order)
+                .ok_or(VerificationError::PointNotInSubgroup {
+                    name,
+                    x: point.x,
+                    y: point.y,
+                })
+        };
+        let left_multiple = multiple("pairing_left", &left_first_term)?;
+        let right_multiple = multiple("pairing_right", &right_first_term)?;
 
-        let mut middle_term_scalar = self.field.add(
-            self.proof.a_bar,
-            self.field.add(
-                self.field.multiply(self.pub_coin.beta, self.pub_coin.zed),
-                self.pub_coin.gamma,
-            ),
-        );
-        middle_term_scalar = self.field.multiply(
-            middle_term_scalar,
-            self.field.add(
-                self.proof.b_bar,
-                self.field.add(
-                    self.field.multiply(
-                        self.field.multiply(self.pub_coin.beta, 2),
-                        self.pub_coin.zed,
-                    ),
-                    self.pub_coin.gamma,
-                ),
-            ),
-        );
-        middle_term_scalar = self.field.multiply(
-            middle_term_scalar,
-            self.field.add(
-                self.proof.c_bar,
-                self.field.add(
-                    self.field.multiply(
-                        self.field.multiply(self.pub_coin.beta, 3),
-                        self.pub_coin.zed,
-                    ),
-                    self.pub_coin.gamma,
-                ),
-            ),
-        );
-        middle_term_scalar = self.field.multiply(
-            middle_term_scalar,
-            self.field.multiply(self.pub_coin.alpha, self.pub_coin.v),
-        );
-        middle_term_scalar = self.field.add(
-            middle_term_scalar,
-            self.field.multiply(
-                self.vals.lagrange_1_opening,
-                self.field
-                    .multiply(self.field.exponent(self.pub_coin.alpha, 2), self.pub_coin.v),
-            ),
-        );
+        let pairing = pairing::Pairing {
+            ecc: ecc.clone(),
+            r: self.field.order,
+        };
 
-        middle_term_scalar = self.field.add(middle_term_scalar, self.pub_coin.u);
-        let middle_term = self.ecc.multiply(middle_term_scalar, &self.proof.z);
+        let mut left_pairing = pairing.get_base_pairing(&self.g_2_points[1], &self.g_1);
+        let mut right_pairing = pairing.get_base_pairing(&self.g_2_points[0], &self.g_1);
 
-        res = self.ecc.add(&res, &middle_term);
+        left_pairing = ComplexScalar::exponent(&ecc.field, &left_pairing, left_multiple);
+        right_pairing = ComplexScalar::exponent(&ecc.field, &right_pairing, right_multiple);
 
-        let mut last_term_scalar = self.field.add(
-            self.proof.a_bar,
-            self.field.add(
-                self.field
-                    .multiply(self.pub_coin.beta, self.proof.left_copy_bar),
-                self.pub_coin.gamma,
-            ),
-        );
-        last_term_scalar = self.field.multiply(
-            last_term_scalar,
-            self.field.add(
-                self.proof.b_bar,
-                self.field.add(
-                    self.field
-                        .multiply(self.pub_coin.beta, self.proof.right_copy_bar),
-                    self.pub_coin.gamma,
-                ),
-            ),
-        );
-        last_term_scalar = self.field.multiply(
-            last_term_scalar,
-            self.field.multiply(
-                self.pub_coin.alpha,
-                self.field.multiply(
-                    self.pub_coin.v,
-                    self.field.multiply(self.pub_coin.beta, self.proof.z_bar),
-                ),
-            ),
-        );
-        last_term_scalar = self.field.additive_inverse(last_term_scalar);
-        let last_term = self
-            .ecc
-            .multiply(last_term_scalar, &self.commitments.output_copy);
+        if !ComplexScalar::equals(&left_pairing, &right_pairing) {
+            return Err(VerificationError::PairingMismatch {
+                left: left_pairing,
+                right: right_pairing,
+            });
+        }
+        Ok(())
+    }
 
-        res = self.ecc.add(&res, &last_term);
-        self.vals.d_commitment = res;
+    // The identity is a valid commitment too, to any polynomial vanishing at s
+    fn in_curve(&self, point: &CurvePoint) -> bool {
+        CurvePoint::equals(point, &CurvePoint::point_at_infinity()) || self.ecc.is_on_curve(point)
     }
+}
 
-    pub fn set_f_commitment(&mut self) {
-        let mut first_term = self.ecc.add(
-            &self.proof.t_lo,
-            &self.ecc.multiply(
-                self.field.exponent(
-                    self.pub_coin.zed,
-                    (self.circuit.circuit.roots.len() + 2) as u32,
-                ),
-                &self.proof.t_mid,
-            ),
-        );
-        first_term = self.ecc.add(
-            &first_term,
-            &self.ecc.multiply(
-                self.field.exponent(
-                    self.pub_coin.zed,
-                    self.field
-                        .multiply(self.circuit.circuit.roots.len() as u32, 2)
-                        + 4,
-                ),
-                &self.proof.t_hi,
-            ),
-        );
+pub fn t_opening(proof: &Proof, coin: &PublicCoin, vals: &VerifierVals) -> FieldElement {
+    let first_term = proof.a_bar + coin.beta * proof.left_copy_bar + coin.gamma;
+    let second_term = proof.b_bar + coin.beta * proof.right_copy_bar + coin.gamma;
+    let third_term = proof.c_bar + coin.gamma;
 
-        let second_term = self.vals.d_commitment.clone();
-
-        let third_term = self.ecc.add(
-            &self
-                .ecc
-                .multiply(self.field.exponent(self.pub_coin.v, 2), &self.proof.a),
-            &self.ecc.add(
-                &self
-                    .ecc
-                    .multiply(self.field.exponent(self.pub_coin.v, 3), &self.proof.b),
-                &self.ecc.add(
-                    &self
-                        .ecc
-                        .multiply(self.field.exponent(self.pub_coin.v, 4), &self.proof.c),
-                    &self.ecc.add(
-                        &self.ecc.multiply(
-                            self.field.exponent(self.pub_coin.v, 5),
-                            &self.commitments.left_copy,
-                        ),
-                        &self.ecc.multiply(
-                            self.field.exponent(self.pub_coin.v, 6),
-                            &self.commitments.right_copy,
-                        ),
-                    ),
-                ),
-            ),
-        );
+    let middle_term = first_term * second_term * third_term * proof.z_bar * coin.alpha;
 
-        self.vals.f_commitment = self
-            .ecc
-            .add(&first_term, &self.ecc.add(&second_term, &third_term));
+    let res = proof.r_bar + vals.public_input_opening
+        - middle_term
+        - vals.lagrange_1_opening * coin.alpha.pow(2);
+
+    res / vals.z_h_opening
+}
+
+// Walks the same steps as verify, keeping the proof and every intermediate value so
+// the article's worked numbers can be checked one step at a time
+impl Verifier {
+    pub fn new<C: CircuitDefinition>(circuit: C, srs: SRS) -> Result<Verifier, DomainError> {
+        let (_, vk) = setup(circuit, &srs)?;
+        Ok(Verifier::from_key(vk))
     }
 
-    pub fn set_e_commitment(&mut self) {
-        let scalar_term = self.field.add(
-            self.vals.t_opening,
-            self.field.add(
-                self.field.multiply(self.pub_coin.v, self.proof.r_bar),
-                self.field.add(
-                    self.field
-                        .multiply(self.field.exponent(self.pub_coin.v, 2), self.proof.a_bar),
-                    self.field.add(
-                        self.field
-                            .multiply(self.field.exponent(self.pub_coin.v, 3), self.proof.b_bar),
-                        self.field.add(
-                            self.field.multiply(
-                                self.field.exponent(self.pub_coin.v, 4),
-                                self.proof.c_bar,
-                            ),
-                            self.field.add(
-                                self.field.multiply(
-                                    self.field.exponent(self.pub_coin.v, 5),
-                                    self.proof.left_copy_bar,
-                                ),
-                                self.field.add(
-                                    self.field.multiply(
-                                        self.field.exponent(self.pub_coin.v, 6),
-                                        self.proof.right_copy_bar,
-                                    ),
-                                    self.field.multiply(self.pub_coin.u, self.proof.z_bar),
-                                ),
-                            ),
-                        ),
-                    ),
-                ),
-            ),
-        );
+    pub fn from_key(vk: VerifyingKey) -> Verifier {
+        Verifier {
+            vk,
+            pub_coin: Default::default(),
+            proof: Default::default(),
+            public_inputs: Vec::new(),
+            vals: Default::default(),
+            fixed_challenges: false,
+        }
+    }
 
-        self.vals.e_commitment = self.ecc.multiply(scalar_term, &self.srs.g_1);
+    pub fn verify_proof(&mut self) -> bool {
+        self.verify().is_ok()
     }
 
-    pub fn check_pairing(&mut self) -> bool {
-        let left_first_term = self.ecc.add(
-            &self.proof.w,
-            &self.ecc.multiply(self.pub_coin.u, &self.proof.wz),
-        );
+    // Like verify_proof, but says which check failed
+    pub fn verify(&mut self) -> Result<(), VerificationError> {
+        // Step 1
+        self.verify_commitments_on_curve()?;
 
-        let mut right_first_term = self.ecc.add(
-            &self.ecc.multiply(self.pub_coin.zed, &self.proof.w),
-            &self.ecc.multiply(
-                self.field.multiply(
-                    self.pub_coin.u,
-                    self.field
-                        .multiply(self.pub_coin.zed, self.circuit.circuit.roots[1]),
-                ),
-                &self.proof.wz,
-            ),
-        );
+        // Step 2
+        self.verify_openings_in_field()?;
 
-        right_first_term = self.ecc.add(
-            &right_first_term,
-            &self.ecc.add(
-                &self.vals.f_commitment,
-                &self.ecc.inversion(&self.vals.e_commitment),
-            ),
-        );
+        // Step 3
+        self.verify_public_inputs()?;
 
-        let left_multiple = self.ecc.get_generator_multiple(
-            &left_first_term,
-            &self.srs.g_1_points[0],
-            self.field.order,
-        );
-        let right_multiple = self.ecc.get_generator_multiple(
-            &right_first_term,
-            &self.srs.g_1_points[0],
-            self.field.order,
-        );
+        self.set_challenges();
 
-        let pairing = pairing::Pairing {
-            ecc: self.ecc.clone(),
-            r: self.field.order,
-        };
+        // Step 4
+        self.set_z_h_opening();
 
-        let mut left_pairing =
-            pairing.get_base_pairing(&self.srs.g_2_points[1], &self.srs.g_1_points[0]);
-        let mut right_pairing =
-            pairing.get_base_pairing(&self.srs.g_2_points[0], &self.srs.g_1_points[0]);
+        // Step 5
+        self.set_lagrange_1_opening();
+
+        // Step 6
+        self.set_public_input_opening();
+
+        // Step 7
+        self.set_t_opening();
 
-        left_pairing = ComplexScalar::exponent(&self.ecc.field, &left_pairing, left_multiple);
-        right_pairing = ComplexScalar::exponent(&self.ecc.field, &right_pairing, right_multiple);
+        // Step 8
+        self.set_d_commitment();
 
-        ComplexScalar::equals(&left_pairing, &right_pairing)
+        // Step 9
+        self.set_f_commitment();
+
+        // Step 10
+        self.set_e_commitment();
+
+        // Step 11
+        self.check_pairing()
     }
 
-    fn in_scalar_field(&self, scalar: u32) -> bool {
-        scalar < self.field.order
+    pub fn provide_proof(&mut self, proof: Proof) {
+        self.proof = proof;
     }
 
-    fn in_curve(&self, point: &CurvePoint) -> bool {
-        if point.x >= self.ecc.field.order || point.y >= self.ecc.field.order {
-            return false;
+    // The values of the circuit's public wires, in the order they were inserted
+    pub fn provide_public_inputs(&mut self, public_inputs: &[u64]) {
+        self.public_inputs = public_inputs.to_vec();
+    }
+
+    // Checks the proof against the article's fixed challenges instead of recomputing
+    // them from the transcript. Only for proofs made with Prover::set_public_coin, and
+    // not sound, so tests only.
+    #[cfg(test)]
+    pub fn set_public_coin(&mut self, public_coin: PublicCoin) {
+        self.pub_coin = public_coin;
+        self.fixed_challenges = true;
+    }
+
+    pub fn set_challenges(&mut self) {
+        if !self.fixed_challenges {
+            self.pub_coin = self.vk.challenges(&self.public_inputs, &self.proof);
         }
-        // must satisfy y^2 = x^3 + 3
-        let left = self.ecc.field.exponent(point.y, 2);
-        let right = self.ecc.field.add(self.ecc.field.exponent(point.x, 3), 3);
+    }
 
-        left == right
+    pub fn verify_commitments_on_curve(&self) -> Result<(), VerificationError> {
+        self.vk.verify_commitments_on_curve(&self.proof)
     }
 
-    fn commit_poly(&self, poly: &Polynomial) -> CurvePoint {
-        let mut res = CurvePoint::point_at_infinity();
+    pub fn verify_openings_in_field(&self) -> Result<(), VerificationError> {
+        self.vk.verify_openings_in_field(&self.proof)
+    }
 
-        for i in 0..poly.coefficients.len() {
-            res = self.ecc.add(
-                &res,
-                &self
-                    .ecc
-                    .multiply(poly.coefficients[i], &self.srs.g_1_points[i]),
-            );
-        }
+    pub fn verify_public_inputs(&self) -> Result<(), VerificationError> {
+        self.vk.verify_public_inputs(&self.public_inputs)
+    }
 
-        res
+    pub fn set_z_h_opening(&mut self) {
+        self.vals.z_h_opening = self.vk.z_h_opening(self.pub_coin.zed);
     }
-}
 
-fn test_setup_verifier_with_proof() -> Verifier {
-    let field_17 = constants::FIELD_17.clone();
+    pub fn set_lagrange_1_opening(&mut self) {
+        self.vals.lagrange_1_opening = self.vk.lagrange_1_opening(self.pub_coin.zed);
+    }
+
+    pub fn set_public_input_opening(&mut self) {
+        self.vals.public_input_opening = self
+            .vk
+            .public_input_opening(&self.public_inputs, self.pub_coin.zed);
+    }
+
+    pub fn set_t_opening(&mut self) {
+        self.vals.t_opening = t_opening(&self.proof, &self.pub_coin, &self.vals);
+    }
 
-    let mut srs = constants::SRS_BY_HAND;
-    srs.generate_g_1_points();
-    srs.generate_g_2_points();
+    pub fn set_d_commitment(&mut self) {
+        self.vals.d_commitment = self
+            .vk
+            .d_commitment(&self.proof, &self.pub_coin, &self.vals);
+    }
 
+    pub fn set_f_commitment(&mut self) {
+        self.vals.f_commitment = self
+            .vk
+            .f_commitment(&self.proof, &self.pub_coin, &self.vals);
+    }
+
+    pub fn set_e_commitment(&mut self) {
+        self.vals.e_commitment = self
+            .vk
+            .e_commitment(&self.proof, &self.pub_coin, &self.vals);
+    }
+
+    pub fn check_pairing(&self) -> Result<(), VerificationError> {
+        self.vk
+            .check_pairing(&self.proof, &self.pub_coin, &self.vals)
+    }
+}
+
+#[cfg(test)]
+fn test_setup_verifier_with_proof() -> Verifier {
+    let field_17 = constants::FIELD_17;
+    let srs = test_utils::srs_by_hand();
     let pub_coin = constants::PUB_COIN.clone();
 
-    const A: u32 = 3;
-    const B: u32 = 4;
-    const C: u32 = 5;
+    const A: u64 = 3;
+    const B: u64 = 4;
+    const C: u64 = 5;
 
-    let mut prover = Prover::new(field_17.clone(), vec![A, B, C], srs.copy());
+    let mut prover =
+        Prover::new(PythagoreanCircuit::new(field_17), vec![A, B, C], srs.copy()).unwrap();
     prover.set_public_coin(pub_coin.clone());
+    prover.set_blinding_rng(Box::new(FixedBlinding::new(&constants::BLINDING_BY_HAND)));
 
-    let mut verifier = Verifier::new(field_17, srs.copy());
-    verifier.preprocess();
-    verifier.provide_proof(pub_coin, prover.generate_proof());
+    let mut verifier = Verifier::new(PythagoreanCircuit::new(field_17), srs.copy()).unwrap();
+    verifier.set_public_coin(pub_coin);
+    verifier.provide_proof(prover.generate_proof().unwrap());
+    verifier.set_challenges();
     verifier
 }
 
 #[test]
-fn test_preprocess() {
+fn test_verifying_key() {
     let verifier = test_setup_verifier_with_proof();
 
     assert!(CurvePoint::equals(
-        &verifier.commitments.left_selector,
+        &verifier.vk.commitments.left_selector,
         &CurvePoint::new(32, 42)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.right_selector,
+        &verifier.vk.commitments.right_selector,
         &CurvePoint::new(32, 42)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.output_selector,
+        &verifier.vk.commitments.output_selector,
         &CurvePoint::new(1, 99)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.multiply_selector,
+        &verifier.vk.commitments.multiply_selector,
         &CurvePoint::new(12, 69)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.c_selector,
+        &verifier.vk.commitments.c_selector,
         &CurvePoint::point_at_infinity()
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.left_copy,
+        &verifier.vk.commitments.left_copy,
         &CurvePoint::new(68, 74)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.right_copy,
+        &verifier.vk.commitments.right_copy,
         &CurvePoint::new(65, 3)
     ));
     assert!(CurvePoint::equals(
-        &verifier.commitments.output_copy,
+        &verifier.vk.commitments.output_copy,
         &CurvePoint::new(18, 49)
     ));
 }
@@ -572,14 +845,14 @@ fn test_preprocess() {
 fn test_verify_commitments_in_field() {
     let verifier = test_setup_verifier_with_proof();
 
-    assert!(verifier.verify_commitments_on_curve());
+    assert_eq!(verifier.verify_commitments_on_curve(), Ok(()));
 }
 
 #[test]
 fn test_verify_openings_in_field() {
     let verifier = test_setup_verifier_with_proof();
 
-    assert!(verifier.verify_openings_in_field());
+    assert_eq!(verifier.verify_openings_in_field(), Ok(()));
 }
 
 #[test]
@@ -587,6 +860,7 @@ fn test_set_z_h_and_lagrange_opening() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     assert_eq!(12, verifier.vals.z_h_opening);
     assert_eq!(5, verifier.vals.lagrange_1_opening);
 }
@@ -596,6 +870,7 @@ fn test_set_t_opening() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     verifier.set_t_opening();
     assert_eq!(1, verifier.vals.t_opening);
 }
@@ -605,6 +880,7 @@ fn test_d_commitment() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     verifier.set_t_opening();
     verifier.set_d_commitment();
     assert!(CurvePoint::equals(
@@ -618,6 +894,7 @@ fn test_f_commitment() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     verifier.set_t_opening();
     verifier.set_d_commitment();
     verifier.set_f_commitment();
@@ -632,6 +909,7 @@ fn test_e_commitment() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     verifier.set_t_opening();
     verifier.set_d_commitment();
     verifier.set_f_commitment();
@@ -647,10 +925,232 @@ fn test_pairing() {
     let mut verifier = test_setup_verifier_with_proof();
     verifier.set_z_h_opening();
     verifier.set_lagrange_1_opening();
+    verifier.set_public_input_opening();
     verifier.set_t_opening();
     verifier.set_d_commitment();
     verifier.set_f_commitment();
     verifier.set_e_commitment();
 
-    assert!(verifier.check_pairing());
+    assert_eq!(verifier.check_pairing(), Ok(()));
+}
+
+#[test]
+fn test_fiat_shamir_proof() {
+    let field_17 = constants::FIELD_17;
+    let srs = test_utils::srs_by_hand();
+
+    // in F_17 some seeds give commitments at the point at infinity, which still verify
+    let mut verifier = Verifier::new(PythagoreanCircuit::new(field_17), srs.copy()).unwrap();
+    for seed in 0..8 {
+        let mut prover =
+            Prover::new(PythagoreanCircuit::new(field_17), vec![3, 4, 5], srs.copy()).unwrap();
+        prover.set_blinding_rng(Box::new(SeededRng::new(seed)));
+        verifier.provide_proof(prover.generate_proof().unwrap());
+        assert!(verifier.verify_proof());
+    }
+
+    // the challenges come from the transcript now, not from the article
+    let transcript_challenges = [
+        verifier.pub_coin.beta,
+        verifier.pub_coin.gamma,
+        verifier.pub_coin.alpha,
+        verifier.pub_coin.zed,
+    ];
+    let article_challenges = [
+        constants::PUB_COIN.beta,
+        constants::PUB_COIN.gamma,
+        constants::PUB_COIN.alpha,
+        constants::PUB_COIN.zed,
+    ];
+    assert_ne!(transcript_challenges, article_challenges);
+
+    // changing any opening changes v and u, so the pairing check no longer holds
+    verifier.proof.a_bar += field_17.one();
+    assert!(!verifier.verify_proof());
+}
+
+#[test]
+fn test_challenges_bind_verifying_key() {
+    let field_17 = constants::FIELD_17;
+    let (vk, proof) = test_utils::prove(PythagoreanCircuit::new(field_17), vec![3, 4, 5], 0);
+    let proof = proof.unwrap();
+    let challenges = |vk: &VerifyingKey| {
+        let coin = vk.challenges(&[], &proof);
+        [coin.beta, coin.gamma, coin.alpha, coin.zed, coin.v]
+    };
+
+    // a key for another circuit or coset must not replay the same challenges
+    let mut other = vk.clone();
+    other.commitments.c_selector = vk.commitments.multiply_selector.clone();
+    assert_ne!(challenges(&other), challenges(&vk));
+
+    let mut other = vk.clone();
+    other.k1 += field_17.one();
+    assert_ne!(challenges(&other), challenges(&vk));
+}
+
+#[test]
+fn test_verification_errors() {
+    let field_17 = constants::FIELD_17;
+
+    let mut verifier = test_setup_verifier_with_proof();
+    verifier.proof.t_mid = CurvePoint::new(1, 1);
+    assert_eq!(
+        verifier.verify(),
+        Err(VerificationError::PointNotOnCurve {
+            name: "t_mid",
+            x: 1,
+            y: 1
+        })
+    );
+
+    let mut verifier = test_setup_verifier_with_proof();
+    verifier.proof.r_bar = FieldElement {
+        value: 17,
+        field: field_17,
+    };
+    assert_eq!(
+        verifier.verify(),
+        Err(VerificationError::OpeningOutOfRange {
+            name: "r_bar",
+            value: 17
+        })
+    );
+
+    let mut verifier = test_setup_verifier_with_proof();
+    verifier.proof.b_bar = Field::new(19).element(3);
+    assert_eq!(
+        verifier.verify(),
+        Err(VerificationError::MalformedProof { name: "b_bar" })
+    );
+
+    let mut verifier = test_setup_verifier_with_proof();
+    verifier.provide_public_inputs(&[1]);
+    assert_eq!(
+        verifier.verify(),
+        Err(VerificationError::WrongPublicInputCount {
+            expected: 0,
+            actual: 1
+        })
+    );
+
+    let mut verifier = test_setup_verifier_with_proof();
+    verifier.proof.z_bar += field_17.one();
+    assert!(matches!(
+        verifier.verify(),
+        Err(VerificationError::PairingMismatch { .. })
+    ));
+    assert!(!verifier.verify_proof());
+}
+
+#[test]
+fn test_verify_with_key() {
+    let field_17 = constants::FIELD_17;
+    let pythagorean = || PythagoreanCircuit::new(field_17);
+
+    let (vk, _) = test_utils::prove(pythagorean(), vec![3, 4, 5], 0);
+    let proofs: Vec<Proof> = (0..4)
+        .map(|seed| {
+            test_utils::prove(pythagorean(), vec![3, 4, 5], seed)
+                .1
+                .unwrap()
+        })
+        .collect();
+
+    // one key, shared by every thread
+    std::thread::scope(|scope| {
+        for proof in &proofs {
+            let vk = &vk;
+            scope.spawn(move || assert_eq!(verify(vk, &[], proof), Ok(())));
+        }
+    });
+
+    let mut tampered = proofs[0].clone();
+    tampered.c_bar += field_17.one();
+    assert!(matches!(
+        verify(&vk, &[], &tampered),
+        Err(VerificationError::PairingMismatch { .. })
+    ));
+    assert_eq!(verify(&vk, &[], &proofs[0]), Ok(()));
+}
+
+#[test]
+fn test_small_order_commitments() {
+    let field_17 = constants::FIELD_17;
+    let (vk, proof) = test_utils::prove(PythagoreanCircuit::new(field_17), vec![3, 4, 5], 0);
+    let proof = proof.unwrap();
+
+    // a 3-torsion w with wz at infinity took exponent 0 on both sides of the pairing,
+    // so some choice of openings used to pass
+    for value in 0..17 {
+        let mut forged = proof.clone();
+        forged.w = CurvePoint::new(27, 30);
+        forged.wz = CurvePoint::point_at_infinity();
+        forged.r_bar = field_17.element(value);
+        forged.a_bar = field_17.element(16 - value);
+        assert_eq!(
+            verify(&vk, &[], &forged),
+            Err(VerificationError::PointNotInSubgroup {
+                name: "w",
+                x: 27,
+                y: 30
+            })
+        );
+    }
+
+    // doubling the order 2 point used to divide by zero
+    let mut forged = proof.clone();
+    forged.w = CurvePoint::new(48, 0);
+    assert_eq!(
+        verify(&vk, &[], &forged),
+        Err(VerificationError::PointNotInSubgroup {
+            name: "w",
+            x: 48,
+            y: 0
+        })
+    );
+}
+
+#[test]
+fn test_verifying_key_json() {
+    let field_17 = constants::FIELD_17;
+    let ecc = constants::SRS_BY_HAND.ecc;
+
+    let (vk, proof) = test_utils::prove(PythagoreanCircuit::new(field_17), vec![3, 4, 5], 5);
+    let text = vk.to_json();
+    assert!(text.contains("\"c_selector\": null"));
+    assert!(text.contains("\"output_copy\": { \"x\": \"0x12\", \"y\": \"0x31\" }"));
+
+    let parsed = VerifyingKey::from_json(&text, &ecc).unwrap();
+    assert_eq!(parsed.to_json(), text);
+
+    // the parsed key still checks proofs
+    assert_eq!(verify(&parsed, &[], &proof.unwrap()), Ok(()));
+
+    let wrong_generator = text.replace("\"generator\": \"0x4\"", "\"generator\": \"0xd\"");
+    assert_ne!(wrong_generator, text);
+    assert_eq!(
+        VerifyingKey::from_json(&wrong_generator, &ecc).err(),
+        Some(JsonError::Inconsistent("generator".to_string()))
+    );
+
+    let replace = |from: &str, to: &str| {
+        let replaced = text.replace(from, to);
+        assert_ne!(replaced, text);
+        VerifyingKey::from_json(&replaced, &ecc).err()
+    };
+    assert_eq!(
+        replace("\"scalar_field\": \"0x11\"", "\"scalar_field\": \"0x0\""),
+        Some(JsonError::InvalidField("scalar_field".to_string()))
+    );
+
+    // 4 is in H, and 8 = 2 * 4 shares k1's coset
+    assert_eq!(
+        replace("\"k1\": \"0x2\"", "\"k1\": \"0x4\""),
+        Some(JsonError::Inconsistent("k1".to_string()))
+    );
+    assert_eq!(
+        replace("\"k2\": \"0x3\"", "\"k2\": \"0x8\""),
+        Some(JsonError::Inconsistent("k2".to_string()))
+    );
 }